@@ -0,0 +1,57 @@
+//! Spawn-heavy workloads, to keep an eye on the per-job overhead of a scope.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use futures::{executor::block_on, stream::FuturesUnordered, StreamExt};
+
+const JOBS: [u32; 2] = [100, 10_000];
+
+/// Spawns `n` trivial jobs and awaits all of them.
+fn spawn_and_await(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn_and_await");
+    for n in JOBS {
+        group.bench_with_input(BenchmarkId::new("scope", n), &n, |b, &n| {
+            b.iter(|| {
+                block_on(moro::async_scope!(|scope| {
+                    let jobs: Vec<_> = (0..n).map(|i| scope.spawn(async move { i })).collect();
+                    let mut sum = 0u64;
+                    for job in jobs {
+                        sum += u64::from(job.await);
+                    }
+                    sum
+                }))
+            })
+        });
+
+        // The same futures without a scope, as a lower bound.
+        group.bench_with_input(BenchmarkId::new("futures_unordered", n), &n, |b, &n| {
+            b.iter(|| {
+                block_on(async {
+                    let jobs: FuturesUnordered<_> = (0..n).map(|i| async move { i }).collect();
+                    jobs.fold(0u64, |sum, i| async move { sum + u64::from(i) })
+                        .await
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Spawns `n` trivial jobs whose handles are dropped; the scope awaits them.
+fn spawn_detached(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn_detached");
+    for n in JOBS {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter(|| {
+                block_on(moro::async_scope!(|scope| {
+                    for i in 0..n {
+                        scope.spawn(async move { std::hint::black_box(i) });
+                    }
+                }))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, spawn_and_await, spawn_detached);
+criterion_main!(benches);
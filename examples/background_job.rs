@@ -0,0 +1,45 @@
+//! A scope whose body returns the result of a primary job, while a
+//! background job (here, flushing metrics) keeps running.
+//!
+//! The body never awaits the background job, and even drops its handle.
+//! That is fine: a scope is only complete once its body *and* all of its
+//! jobs have completed, so awaiting the scope waits for the background job
+//! too, and the body's result is returned afterwards. Only terminating the
+//! scope (e.g. with `scope.terminate(..)`) stops jobs early.
+
+use std::sync::Mutex;
+
+#[tokio::main]
+async fn main() {
+    let metrics = Mutex::new(vec![]);
+    let answer = handle(&metrics).await;
+    eprintln!("answer: {answer}, metrics: {:?}", metrics.lock().unwrap());
+}
+
+/// Computes an answer, recording metrics in the background.
+pub async fn handle(metrics: &Mutex<Vec<String>>) -> u32 {
+    moro::async_scope!(|scope| {
+        let answer = scope.spawn(async {
+            tokio::task::yield_now().await;
+            22
+        });
+
+        // Not awaited by the body; the scope still waits for it.
+        drop(scope.spawn(async {
+            for step in ["connected", "flushed", "closed"] {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                metrics.lock().unwrap().push(step.to_string());
+            }
+        }));
+
+        answer.await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn background_job_completes_before_scope() {
+    let metrics = Mutex::new(vec![]);
+    assert_eq!(handle(&metrics).await, 22);
+    assert_eq!(*metrics.lock().unwrap(), ["connected", "flushed", "closed"]);
+}
@@ -0,0 +1,57 @@
+//! Spawning a job per item versus per batch of items.
+//!
+//! Each job carries some overhead in the scope: it is boxed, registered,
+//! and polled on its own. When the work per item is tiny, that overhead
+//! dominates, and `spawn_batched` is much faster than spawning a job per
+//! item. Run with `cargo run --release --example batched` to compare.
+
+use std::time::Instant;
+
+const ITEMS: u64 = 200_000;
+
+#[tokio::main]
+async fn main() {
+    let start = Instant::now();
+    let per_item = per_item(ITEMS).await;
+    eprintln!("one job per item:   {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let batched = batched(ITEMS, 1024).await;
+    eprintln!("one job per batch:  {:?}", start.elapsed());
+
+    assert_eq!(per_item, batched);
+}
+
+fn work(n: u64) -> u64 {
+    n.wrapping_mul(0x9e37_79b9_7f4a_7c15).rotate_left(17)
+}
+
+/// Spawns a job per item.
+pub async fn per_item(items: u64) -> Vec<u64> {
+    moro::async_scope!(|scope| {
+        let jobs = (0..items).map(|n| async move { work(n) });
+        moro::join_all(scope, jobs).await
+    })
+    .await
+}
+
+/// Spawns a job per batch of `batch_size` items.
+pub async fn batched(items: u64, batch_size: usize) -> Vec<u64> {
+    moro::async_scope!(|scope| {
+        scope
+            .spawn_batched(0..items, batch_size, |batch| async move {
+                batch.into_iter().map(work).collect()
+            })
+            .await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn batched_results_are_in_input_order() {
+    let expected: Vec<u64> = (0..1000).map(work).collect();
+    for batch_size in [1, 7, 1000, 5000] {
+        assert_eq!(batched(1000, batch_size).await, expected);
+    }
+    assert_eq!(per_item(1000).await, expected);
+}
@@ -0,0 +1,33 @@
+// A batch processor that is interrupted by a deadline. Jobs that did not
+// finish in time are recovered rather than lost, and resumed in a second run.
+
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() {
+    let batch = vec![10, 50, 200, 400];
+
+    let (result, pending) = moro::async_scope!(|scope| {
+        for &millis in &batch {
+            scope.spawn_restartable(move || process(millis));
+        }
+    })
+    .drain_or_recover(tokio::time::sleep(Duration::from_millis(100)))
+    .await;
+    eprintln!("first run completed: {}", result.is_some());
+    eprintln!("{} jobs left to resume", pending.len());
+
+    // A real application might persist the pending work here.
+    moro::async_scope!(|scope| {
+        for job in &pending {
+            scope.spawn(job.start());
+        }
+    })
+    .await;
+    eprintln!("All done");
+}
+
+async fn process(millis: u64) {
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    eprintln!("processed item taking {millis}ms");
+}
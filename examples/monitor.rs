@@ -2,19 +2,38 @@
 async fn main() {
     eprintln!("all positive {:?}", run(&vec![1, 2, 10]).await);
     eprintln!("some negative {:?}", run(&vec![1, 2, -3, 10]).await);
+    eprintln!(
+        "distinct errors {:?}",
+        run_dedup(&[1, -3, 2, -3, 10, -4]).await
+    );
 }
 
 /// Run the simulated journal.
 pub async fn run(inputs: &Vec<i32>) -> anyhow::Result<()> {
     moro::async_scope!(|scope| {
         for input in inputs {
-            let _ = scope.spawn(validate(input)).or_cancel(scope);
+            drop(scope.spawn_checked(validate(input)));
         }
         Ok(())
     })
     .await
 }
 
+/// Run the simulated journal, validating every input and reporting each
+/// distinct error once.
+pub async fn run_dedup(inputs: &[i32]) -> Vec<String> {
+    moro::async_scope!(|scope| {
+        scope
+            .validate_all_dedup(
+                inputs
+                    .iter()
+                    .map(|input| async move { validate(input).await.map_err(|e| e.to_string()) }),
+            )
+            .await
+    })
+    .await
+}
+
 pub async fn validate(input: &i32) -> anyhow::Result<()> {
     if *input < 0 {
         anyhow::bail!("input out of range: {input}");
@@ -34,3 +53,11 @@ async fn some_negative() {
         Ok(()) => panic!("expected an error"),
     }
 }
+
+#[tokio::test]
+async fn duplicate_errors_reported_once() {
+    assert_eq!(
+        run_dedup(&[-3, 1, -3, -3, 2]).await,
+        vec!["input out of range: -3".to_string()]
+    );
+}
@@ -0,0 +1,34 @@
+//! Sums a large slice on rayon's thread pool from within a scope:
+//!
+//! ```text
+//! cargo run --example parallel_sum --features rayon
+//! ```
+
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+#[tokio::main]
+async fn main() {
+    let data: Arc<[u64]> = (0..10_000_000).collect();
+    eprintln!("sum {}", run(data).await);
+}
+
+/// Sums the two halves of `data` as separate parallel jobs; the async side
+/// of the scope stays free to run other jobs meanwhile.
+pub async fn run(data: Arc<[u64]>) -> u64 {
+    moro::async_scope!(|scope| {
+        let mid = data.len() / 2;
+        let front = data.clone();
+        let front = scope.spawn_rayon(move || front[..mid].par_iter().sum::<u64>());
+        let back = scope.spawn_rayon(move || data[mid..].par_iter().sum::<u64>());
+        front.await + back.await
+    })
+    .await
+}
+
+#[tokio::test]
+async fn sums_in_parallel() {
+    let data: Arc<[u64]> = (1..=1_000_000).collect();
+    assert_eq!(run(data).await, 500_000_500_000);
+}
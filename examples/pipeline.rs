@@ -0,0 +1,33 @@
+// A classic three-stage pipeline: "read" lines, transform them, and "write"
+// them out. Each stage runs as its own job in the scope, and at most
+// `PREFETCH` items are buffered between two stages.
+
+use moro::AsyncIterator;
+
+const PREFETCH: usize = 2;
+
+#[tokio::main]
+async fn main() {
+    let lines = ["hello", "structured", "concurrency"];
+    let written = moro::async_scope!(|scope| {
+        let mut output = moro::pipeline(scope, lines, PREFETCH)
+            .stage(|line| async move {
+                eprintln!("read {line:?}");
+                tokio::task::yield_now().await;
+                line.to_string()
+            })
+            .stage(|line| async move {
+                eprintln!("transform {line:?}");
+                line.to_uppercase()
+            });
+
+        let mut written = 0;
+        while let Some(line) = output.next().await {
+            eprintln!("write {line:?}");
+            written += 1;
+        }
+        written
+    })
+    .await;
+    eprintln!("{written} lines written");
+}
@@ -2,44 +2,109 @@
 // Replicas are connected via fixed-width message queues.
 // Using FuturesUnordered, easy to deadlock, as shown [here](https://play.rust-lang.org/?version=stable&mode=debug&edition=2021&gist=66455d3e61217d86ec4839c926619bf0).
 // Based on real-life bug.
+//
+// Each replica yields every message it receives into the scope's outputs,
+// which the scope body consumes as they arrive.
+//
+// Sending every message before consuming any output still deadlocks once the
+// queues are small enough: the replicas block yielding outputs, stop
+// receiving, and the sends block on full queues. `send_all_then_drain`
+// consumes outputs while sends wait, which avoids this.
+//
+// Each replica also returns how many characters it received; `run` gathers
+// these per host, in spawn order, with `JobSet::join_ordered`.
 
-use tokio::sync::mpsc::channel;
+use moro::{AsyncIterator, JobSet, Yielder};
+use tokio::sync::mpsc::{channel, Sender};
+
+const MESSAGE: [char; 6] = ['H', 'e', 'l', 'l', 'o', '\n'];
 
 #[tokio::main]
 async fn main() {
+    let (received, counts) = run(3, 1).await;
+    for (host, message) in received {
+        eprintln!("Host {host} received message {message:?}");
+    }
+    for (host, count) in counts {
+        eprintln!("Host {host} received {count} characters");
+    }
+
+    eprintln!("All done")
+}
+
+/// Sends the message to `replicas` replicas, with queues of `capacity`
+/// messages, and returns the messages as the replicas received them,
+/// together with each replica's character count, by host.
+pub async fn run(replicas: u32, capacity: usize) -> (Vec<(u32, char)>, Vec<(u32, usize)>) {
     moro::async_scope!(|scope| {
-        // Start up the replicas
-        let replicas = 3;
-        let mut host_senders = vec![];
-        let mut host_futures = vec![];
-        for host in 0..replicas {
-            let (sender, receiver) = channel(222);
-            host_senders.push(sender);
-            host_futures.push(scope.spawn(replica(host, receiver)));
-        }
+        let mut counts = scope.job_set();
+        let host_senders = start(scope, &mut counts, replicas, capacity);
+
+        let sends = MESSAGE.into_iter().flat_map(move |message| {
+            host_senders
+                .clone()
+                .into_iter()
+                .map(move |sender| async move {
+                    sender.send(message).await.unwrap();
+                })
+        });
+        let outputs = scope.outputs::<(u32, char)>();
+        let received = scope.send_all_then_drain(sends, outputs).await;
+        (received, counts.join_ordered().await)
+    })
+    .await
+}
+
+/// Like [`run`], but sends all messages before consuming any output.
+/// Deadlocks if `capacity` is too small.
+pub async fn run_naive(replicas: u32, capacity: usize) -> Vec<(u32, char)> {
+    moro::async_scope!(|scope| {
+        let host_senders = start(scope, &mut scope.job_set(), replicas, capacity);
 
         // Send the data
-        for message in ['H', 'e', 'l', 'l', 'o', '\n'] {
+        for message in MESSAGE {
             for sender in &host_senders {
                 sender.send(message).await.unwrap();
             }
         }
+        drop(host_senders);
 
-        // Drain the replicas.
-        for future in host_futures {
-            let (host, count) = future.await;
-            eprintln!("Host {host} received {count} bytes.");
+        // Observe the messages as the replicas receive them.
+        let mut outputs = scope.outputs::<(u32, char)>();
+        let mut received = vec![];
+        while let Some(output) = outputs.next().await {
+            received.push(output);
         }
+        received
     })
-    .await;
+    .await
+}
 
-    eprintln!("All done")
+/// Starts up the replicas and returns the senders of their queues. Each
+/// replica's result is spawned into `counts`.
+fn start<'scope, 'env, R: Send>(
+    scope: &'scope moro::Scope<'scope, 'env, R>,
+    counts: &mut JobSet<'scope, 'env, R, (u32, usize)>,
+    replicas: u32,
+    capacity: usize,
+) -> Vec<Sender<char>> {
+    let mut host_senders = vec![];
+    for host in 0..replicas {
+        let (sender, receiver) = channel(capacity);
+        host_senders.push(sender);
+        counts.spawn(scope.spawn_yielding(move |yielder| replica(host, receiver, yielder)));
+    }
+    host_senders
 }
 
-async fn replica(host: u32, mut receiver: tokio::sync::mpsc::Receiver<char>) -> (u32, usize) {
+async fn replica(
+    host: u32,
+    mut receiver: tokio::sync::mpsc::Receiver<char>,
+    yielder: Yielder<'_, (u32, char)>,
+) -> (u32, usize) {
     let mut count = 0;
     while let Some(message) = receiver.recv().await {
-        eprintln!("Host {host} received message {message:?}");
+        yielder.yield_((host, message)).await;
         if message == '\n' {
             break;
         } else {
@@ -48,3 +113,60 @@ async fn replica(host: u32, mut receiver: tokio::sync::mpsc::Receiver<char>) ->
     }
     (host, count)
 }
+
+/// Polls `future` until it completes or is pending without having woken its
+/// task, i.e. can make no more progress: nothing outside of it, such as a
+/// timer, could wake it up.
+#[cfg(test)]
+fn is_deadlocked(future: impl std::future::Future) -> bool {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use futures::task::{waker, ArcWake};
+
+    struct Woken(AtomicBool);
+    impl ArcWake for Woken {
+        fn wake_by_ref(this: &Arc<Self>) {
+            this.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let woken = Arc::new(Woken(AtomicBool::new(true)));
+    let waker = waker(woken.clone());
+    let mut cx = std::task::Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    while woken.0.swap(false, Ordering::SeqCst) {
+        if future.as_mut().poll(&mut cx).is_ready() {
+            return false;
+        }
+    }
+    true
+}
+
+#[test]
+fn naive_sending_deadlocks_with_small_queues() {
+    assert!(is_deadlocked(run_naive(3, 1)));
+    assert!(!is_deadlocked(run_naive(3, 222)));
+}
+
+#[tokio::test]
+async fn naive_sending_completes_with_large_queues() {
+    assert_eq!(run_naive(3, 222).await.len(), 18);
+}
+
+#[tokio::test]
+async fn draining_while_sending_avoids_deadlock() {
+    let (received, counts) = run(3, 1).await;
+    assert_eq!(received.len(), 18);
+    assert_eq!(counts, [(0, 5), (1, 5), (2, 5)]);
+    for host in 0..3 {
+        let messages: String = received
+            .iter()
+            .filter(|(h, _)| *h == host)
+            .map(|(_, m)| m)
+            .collect();
+        assert_eq!(messages, "Hello\n");
+    }
+}
@@ -0,0 +1,126 @@
+//! Per-request structured concurrency, as a web server handler would use it.
+//!
+//! With a framework like axum, a handler's future is dropped when the client
+//! disconnects, and a moro scope inside it is dropped along with it. But a
+//! handler that has already produced its response headers and keeps
+//! streaming, or one running on a detached task, needs to observe the hangup
+//! explicitly. `moro::request_scope` ties a scope to such a disconnect
+//! signal: every sub-task spawned to answer the request (database queries,
+//! upstream calls) is cancelled as soon as the client goes away.
+//!
+//! To keep the example self-contained, the server is simulated: a request is
+//! a user name plus a oneshot receiver that fires on hangup, and the
+//! database's latency is simulated with a [`moro::Clock`].
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use moro::{Clock, SystemClock};
+use tokio::sync::oneshot;
+
+#[tokio::main]
+async fn main() {
+    let stats = Stats::default();
+
+    let (_hangup, disconnected) = oneshot::channel();
+    eprintln!(
+        "patient client: {:?}",
+        handle(&stats, &SystemClock, "alice", disconnected).await
+    );
+
+    let (hangup, disconnected) = oneshot::channel();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(hangup);
+    });
+    eprintln!(
+        "impatient client: {:?}",
+        handle(&stats, &SystemClock, "bob", disconnected).await
+    );
+    eprintln!(
+        "queries started: {}, cancelled: {}",
+        stats.started.load(Ordering::SeqCst),
+        stats.cancelled.load(Ordering::SeqCst)
+    );
+}
+
+/// Counts the simulated queries, so we can observe the cleanup.
+#[derive(Default)]
+pub struct Stats {
+    started: AtomicUsize,
+    cancelled: AtomicUsize,
+}
+
+/// Handles a request for `user`'s page, which needs the results of two
+/// queries. Returns `None` if the client disconnected first.
+pub async fn handle(
+    stats: &Stats,
+    clock: &dyn Clock,
+    user: &str,
+    disconnected: oneshot::Receiver<()>,
+) -> Option<String> {
+    let disconnect = async {
+        // The sender is dropped on hangup; a sent value counts as hangup too.
+        let _ = disconnected.await;
+    };
+    moro::request_scope(disconnect, async |scope| {
+        let profile = scope.spawn(query(stats, clock, format!("profile of {user}"), 5));
+        let feed = scope.spawn(query(stats, clock, format!("feed of {user}"), 50));
+        format!("{} / {}", profile.await, feed.await)
+    })
+    .await
+}
+
+/// Simulates a database query taking `millis` milliseconds on `clock`.
+async fn query(stats: &Stats, clock: &dyn Clock, answer: String, millis: u64) -> String {
+    struct OnCancel<'a>(&'a Stats, bool);
+    impl Drop for OnCancel<'_> {
+        fn drop(&mut self) {
+            if !self.1 {
+                self.0.cancelled.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    stats.started.fetch_add(1, Ordering::SeqCst);
+    let mut guard = OnCancel(stats, false);
+    clock
+        .sleep_until(clock.now() + Duration::from_millis(millis))
+        .await;
+    guard.1 = true;
+    answer
+}
+
+#[tokio::test]
+async fn completes_when_client_waits() {
+    let stats = Stats::default();
+    let clock = moro::test_util::VirtualClock::new();
+    let (_hangup, disconnected) = oneshot::channel();
+    let response = handle(&stats, &clock, "alice", disconnected).await;
+    assert_eq!(
+        response.as_deref(),
+        Some("profile of alice / feed of alice")
+    );
+    assert_eq!(stats.cancelled.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn disconnect_cancels_queries() {
+    let stats = Stats::default();
+    let clock = moro::test_util::ManualClock::new();
+    let (hangup, disconnected) = oneshot::channel();
+    let client = async {
+        // Let the queries start, then hang up once the profile is loaded.
+        moro::test_util::yield_now().await;
+        clock.advance(Duration::from_millis(10));
+        moro::test_util::yield_now().await;
+        drop(hangup);
+    };
+    let (response, ()) = futures::join!(handle(&stats, &clock, "bob", disconnected), client);
+    assert_eq!(response, None);
+    assert_eq!(stats.started.load(Ordering::SeqCst), 2);
+    // The quick profile query finished; the slow feed query was cancelled.
+    assert_eq!(stats.cancelled.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,120 @@
+//! Shows the spans and events emitted with the `tracing` feature:
+//!
+//! ```text
+//! cargo run --example tracing --features tracing
+//! ```
+
+use std::time::Duration;
+
+use tracing_subscriber::fmt::format::FmtSpan;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+        .init();
+    run().await;
+}
+
+/// Fetches a page while a named heartbeat job runs, until the fetch is done.
+pub async fn run() -> &'static str {
+    moro::async_scope!(|scope| {
+        scope.spawn_named("heartbeat", async {
+            loop {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        let page = scope
+            .spawn_named("fetch", async {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                "<html>"
+            })
+            .await;
+        scope.terminate(page).await
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id},
+        Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    /// Records the name and `label` field of every new span, and the
+    /// `location` field of `moro::job` spans.
+    #[derive(Clone, Default)]
+    struct Spans {
+        labels: Arc<Mutex<Vec<(&'static str, Option<String>)>>>,
+        locations: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[derive(Default)]
+    struct Fields {
+        label: Option<String>,
+        location: Option<String>,
+    }
+
+    impl Visit for Fields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "label" {
+                self.label = Some(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "location" {
+                self.location = Some(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for Spans {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            self.labels
+                .lock()
+                .unwrap()
+                .push((attrs.metadata().name(), fields.label));
+            self.locations.lock().unwrap().extend(fields.location);
+        }
+    }
+
+    #[tokio::test]
+    async fn jobs_are_polled_in_labeled_spans() {
+        let spans = Spans::default();
+        let _guard = tracing_subscriber::registry()
+            .with(spans.clone())
+            .set_default();
+
+        assert_eq!(super::run().await, "<html>");
+
+        let labels = spans.labels.lock().unwrap();
+        assert!(labels.contains(&("moro::poll_jobs", None)));
+        assert!(labels.contains(&("moro::job", Some("heartbeat".to_string()))));
+        assert!(labels.contains(&("moro::job", Some("fetch".to_string()))));
+    }
+
+    #[tokio::test]
+    async fn job_spans_record_where_jobs_were_spawned() {
+        let spans = Spans::default();
+        let _guard = tracing_subscriber::registry()
+            .with(spans.clone())
+            .set_default();
+
+        super::run().await;
+
+        let locations = spans.locations.lock().unwrap();
+        assert_eq!(locations.len(), 2);
+        assert!(locations
+            .iter()
+            .all(|location| location.starts_with("examples/tracing.rs:")));
+    }
+}
@@ -0,0 +1,82 @@
+//! Using moro scopes inside trait-based architectures.
+//!
+//! An `async fn` in a trait can simply await a scope. Traits that must be
+//! dyn-compatible cannot use `async fn`, though, and have to name the type
+//! of the future they return; `moro::ScopeFuture` and `moro::scope_boxed!`
+//! provide such a type.
+
+use std::collections::HashMap;
+
+/// A storage backend, used as a trait object.
+pub trait Backend: Send + Sync {
+    fn fetch_all<'a>(
+        &'a self,
+        keys: &'a [&'a str],
+    ) -> moro::ScopeFuture<'a, Result<Vec<u32>, String>>;
+}
+
+/// An in-memory backend that looks up each key in a separate job.
+pub struct Memory(pub HashMap<&'static str, u32>);
+
+impl Backend for Memory {
+    fn fetch_all<'a>(
+        &'a self,
+        keys: &'a [&'a str],
+    ) -> moro::ScopeFuture<'a, Result<Vec<u32>, String>> {
+        let map = &self.0;
+        moro::scope_boxed!(|scope| -> Result<Vec<u32>, String> {
+            let lookups: Vec<_> = keys
+                .iter()
+                .map(|key| {
+                    scope.spawn(async move {
+                        match map.get(key) {
+                            Some(&value) => value,
+                            None => scope.terminate(Err(format!("missing key: {key}"))).await,
+                        }
+                    })
+                })
+                .collect();
+            Ok(futures::future::join_all(lookups).await)
+        })
+    }
+}
+
+/// A service with an `async fn`, whose implementation uses a scope.
+trait Report {
+    async fn total(&self, backend: &dyn Backend) -> Result<u32, String>;
+}
+
+struct Totals(pub Vec<&'static str>);
+
+impl Report for Totals {
+    async fn total(&self, backend: &dyn Backend) -> Result<u32, String> {
+        let values = backend.fetch_all(&self.0).await?;
+        Ok(values.iter().sum())
+    }
+}
+
+fn backend() -> Box<dyn Backend> {
+    Box::new(Memory(HashMap::from([("a", 1), ("b", 2), ("c", 3)])))
+}
+
+#[tokio::main]
+async fn main() {
+    let backend = backend();
+    eprintln!("{:?}", Totals(vec!["a", "b", "c"]).total(&*backend).await);
+    eprintln!("{:?}", Totals(vec!["a", "z"]).total(&*backend).await);
+}
+
+#[tokio::test]
+async fn total_of_present_keys() {
+    let backend = backend();
+    assert_eq!(Totals(vec!["a", "c"]).total(&*backend).await, Ok(4));
+}
+
+#[tokio::test]
+async fn missing_key_cancels_lookups() {
+    let backend = backend();
+    assert_eq!(
+        Totals(vec!["a", "z"]).total(&*backend).await,
+        Err("missing key: z".to_string())
+    );
+}
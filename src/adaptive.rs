@@ -0,0 +1,205 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+/// Parameters of the concurrency limiter used by
+/// [`Scope::spawn_adaptive`][crate::Scope::spawn_adaptive].
+///
+/// The limiter follows an AIMD (additive increase, multiplicative decrease)
+/// policy: each job that completes within `target_latency` raises the limit
+/// by one, up to `max`, and each job that takes longer halves it, down to
+/// `min`.
+#[derive(Copy, Clone, Debug)]
+pub struct AdaptiveConfig {
+    /// Concurrency limit to start with.
+    pub initial: usize,
+
+    /// Lower bound for the limit; at least one.
+    pub min: usize,
+
+    /// Upper bound for the limit.
+    pub max: usize,
+
+    /// Jobs taking longer than this (from being admitted to completing)
+    /// cause the limit to back off.
+    pub target_latency: Duration,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            initial: 1,
+            min: 1,
+            max: 64,
+            target_latency: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Admission control for adaptive jobs.
+pub(crate) struct Adaptive {
+    state: Mutex<AdaptiveState>,
+}
+
+struct AdaptiveState {
+    config: AdaptiveConfig,
+    limit: usize,
+    in_flight: usize,
+
+    /// Jobs waiting to be admitted, in order of arrival, one entry per
+    /// [`Acquire`], keyed by its id. Only the first one is woken when a slot
+    /// frees up; once admitted, it wakes the next one if room remains.
+    waiters: VecDeque<(u64, Waker)>,
+    next_waiter: u64,
+}
+
+impl AdaptiveState {
+    /// Wakes the first waiter if it can be admitted.
+    fn wake_next(&self) {
+        if self.in_flight < self.limit {
+            if let Some((_, waker)) = self.waiters.front() {
+                waker.wake_by_ref();
+            }
+        }
+    }
+}
+
+impl Default for Adaptive {
+    fn default() -> Self {
+        let adaptive = Self {
+            state: Mutex::new(AdaptiveState {
+                config: AdaptiveConfig::default(),
+                limit: 0,
+                in_flight: 0,
+                waiters: VecDeque::new(),
+                next_waiter: 0,
+            }),
+        };
+        adaptive.configure(AdaptiveConfig::default());
+        adaptive
+    }
+}
+
+impl Adaptive {
+    /// A limiter whose limit stays at `limit` (at least one).
+    pub(crate) fn fixed(limit: usize) -> Self {
+        let adaptive = Self::default();
+        adaptive.configure(AdaptiveConfig {
+            initial: limit,
+            min: limit,
+            max: limit,
+            ..AdaptiveConfig::default()
+        });
+        adaptive
+    }
+
+    pub(crate) fn configure(&self, config: AdaptiveConfig) {
+        let mut state = self.state.lock().unwrap();
+        let min = config.min.max(1);
+        state.config = AdaptiveConfig {
+            min,
+            max: config.max.max(min),
+            ..config
+        };
+        state.limit = config.initial.clamp(min, state.config.max);
+        state.wake_next();
+    }
+
+    pub(crate) fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit
+    }
+
+    /// Waits until fewer jobs than the current limit are in flight and all
+    /// earlier callers were admitted, then counts the caller as in flight.
+    /// The returned guard releases the slot.
+    pub(crate) fn acquire(&self) -> Acquire<'_> {
+        Acquire {
+            adaptive: self,
+            waiter: None,
+        }
+    }
+}
+
+/// Future returned by [`Adaptive::acquire`].
+pub(crate) struct Acquire<'a> {
+    adaptive: &'a Adaptive,
+
+    /// Id of our entry in `waiters`, while queued.
+    waiter: Option<u64>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = AdaptivePermit<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<AdaptivePermit<'a>> {
+        let adaptive = self.adaptive;
+        let mut state = adaptive.state.lock().unwrap();
+        let first = match self.waiter {
+            Some(id) => state.waiters.front().is_some_and(|(w, _)| *w == id),
+            None => state.waiters.is_empty(),
+        };
+        if first && state.in_flight < state.limit {
+            state.in_flight += 1;
+            if self.waiter.take().is_some() {
+                state.waiters.pop_front();
+            }
+            state.wake_next();
+            return Poll::Ready(AdaptivePermit {
+                adaptive,
+                latency: None,
+            });
+        }
+        match self.waiter {
+            Some(id) => {
+                let (_, waker) = state.waiters.iter_mut().find(|(w, _)| *w == id).unwrap();
+                waker.clone_from(cx.waker());
+            }
+            None => {
+                let id = state.next_waiter;
+                state.next_waiter += 1;
+                state.waiters.push_back((id, cx.waker().clone()));
+                self.waiter = Some(id);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiter {
+            let mut state = self.adaptive.state.lock().unwrap();
+            state.waiters.retain(|(w, _)| *w != id);
+            // We may have been woken to be admitted; pass that on.
+            state.wake_next();
+        }
+    }
+}
+
+/// An admitted job's slot.
+pub(crate) struct AdaptivePermit<'a> {
+    adaptive: &'a Adaptive,
+
+    /// Set when the job completes; jobs dropped early do not affect the limit.
+    pub(crate) latency: Option<Duration>,
+}
+
+impl Drop for AdaptivePermit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.adaptive.state.lock().unwrap();
+        state.in_flight -= 1;
+        if let Some(latency) = self.latency {
+            state.limit = if latency <= state.config.target_latency {
+                (state.limit + 1).min(state.config.max)
+            } else {
+                (state.limit / 2).max(state.config.min)
+            };
+        }
+        state.wake_next();
+    }
+}
@@ -1,4 +1,14 @@
-use crate::Scope;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{
+    stream::{FusedStream, FuturesOrdered, FuturesUnordered},
+    Future, StreamExt,
+};
+
+use crate::{JobSet, Scope};
 
 pub trait AsyncIterator {
     type Item;
@@ -27,6 +37,832 @@ pub trait AsyncIterator {
             filter_op: op,
         }
     }
+
+    /// Transforms each item with `op`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut iter = moro::from_iter(1..=6)
+    ///     .filter(async |n| n % 2 == 0)
+    ///     .map(async |n| n * 10)
+    ///     .filter(async |n| *n != 40);
+    /// let mut items = vec![];
+    /// while let Some(item) = iter.next().await {
+    ///     items.push(item);
+    /// }
+    /// assert_eq!(items, [20, 60]);
+    /// # });
+    /// ```
+    fn map<U>(self, op: impl async FnMut(Self::Item) -> U) -> impl AsyncIterator<Item = U>
+    where
+        Self: Sized,
+    {
+        Map {
+            iter: self,
+            map_op: op,
+        }
+    }
+
+    /// Maps each item to an iterator with `op`, and yields the items of
+    /// those iterators in turn; like [`Iterator::flat_map`].
+    ///
+    /// The next item of `self` is only fetched, and mapped, once the
+    /// iterator of the previous one is exhausted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let dirs = moro::from_iter([("src", 2), ("empty", 0), ("tests", 1)]);
+    /// let mut entries = vec![];
+    /// dirs.flat_map(async |(dir, len)| moro::from_iter((0..len).map(move |i| format!("{dir}/{i}"))))
+    ///     .for_each(async |entry| entries.push(entry))
+    ///     .await;
+    /// assert_eq!(entries, ["src/0", "src/1", "tests/0"]);
+    /// # });
+    /// ```
+    fn flat_map<J: AsyncIterator>(
+        self,
+        op: impl async FnMut(Self::Item) -> J,
+    ) -> impl AsyncIterator<Item = J::Item>
+    where
+        Self: Sized,
+    {
+        FlatMap {
+            iter: self,
+            map_op: op,
+            inner: None,
+        }
+    }
+
+    /// Like [`filter`][Self::filter], for iterators over `Result`s with a
+    /// fallible predicate.
+    ///
+    /// `Ok` items are kept if `op` returns `Ok(true)` and dropped if it returns
+    /// `Ok(false)`; if `op` fails, its error is yielded in place of the item.
+    /// `Err` items are passed through without calling `op`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let requests = moro::from_iter([Ok("alice"), Ok("bob"), Err("bad request"), Ok("eve")]);
+    /// let mut allowed = requests.try_filter(async |user| match *user {
+    ///     "alice" => Ok(true),
+    ///     "bob" => Ok(false),
+    ///     _ => Err("permission check failed"),
+    /// });
+    /// let mut items = vec![];
+    /// while let Some(item) = allowed.next().await {
+    ///     items.push(item);
+    /// }
+    /// assert_eq!(
+    ///     items,
+    ///     [Ok("alice"), Err("bad request"), Err("permission check failed")]
+    /// );
+    /// # });
+    /// ```
+    fn try_filter<T, E>(
+        self,
+        op: impl async FnMut(&T) -> Result<bool, E>,
+    ) -> impl AsyncIterator<Item = Result<T, E>>
+    where
+        Self: Sized + AsyncIterator<Item = Result<T, E>>,
+    {
+        TryFilter {
+            iter: self,
+            filter_op: op,
+        }
+    }
+
+    /// Yields items while `op` returns true, and ends at the first item for
+    /// which it returns false, which is discarded.
+    ///
+    /// As with [`Iterator::take_while`], the iterator stays exhausted
+    /// afterwards: neither `op` nor the underlying iterator is called again,
+    /// so the items after the failing one are left in the underlying
+    /// iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut messages = moro::from_iter(["a", "b", "\n", "c"]);
+    /// let mut line = vec![];
+    /// (&mut messages)
+    ///     .take_while(async |m| *m != "\n")
+    ///     .for_each(async |m| line.push(m))
+    ///     .await;
+    /// assert_eq!(line, ["a", "b"]);
+    /// assert_eq!(messages.next().await, Some("c"));
+    /// # });
+    /// ```
+    fn take_while(
+        self,
+        op: impl async FnMut(&Self::Item) -> bool,
+    ) -> impl AsyncIterator<Item = Self::Item>
+    where
+        Self: Sized,
+    {
+        TakeWhile {
+            iter: self,
+            take_op: op,
+            done_taking: false,
+        }
+    }
+
+    /// Skips items while `op` returns true, then yields the first item for
+    /// which it returns false and every item after it.
+    ///
+    /// As with [`Iterator::skip_while`], `op` is not called again once it
+    /// has returned false.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut calls = 0;
+    /// let mut iter = moro::from_iter([1, 2, 5, 1, 6]).skip_while(async |&n| {
+    ///     calls += 1;
+    ///     n < 3
+    /// });
+    /// let mut items = vec![];
+    /// while let Some(item) = iter.next().await {
+    ///     items.push(item);
+    /// }
+    /// assert_eq!(items, [5, 1, 6]);
+    /// drop(iter);
+    /// assert_eq!(calls, 3);
+    /// # });
+    /// ```
+    fn skip_while(
+        self,
+        op: impl async FnMut(&Self::Item) -> bool,
+    ) -> impl AsyncIterator<Item = Self::Item>
+    where
+        Self: Sized,
+    {
+        SkipWhile {
+            iter: self,
+            skip_op: op,
+            done_skipping: false,
+        }
+    }
+
+    /// Yields the first `n` items, then ends without fetching any more items
+    /// from `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// /// Counts the items requested from it.
+    /// struct Naturals(u32);
+    ///
+    /// impl AsyncIterator for Naturals {
+    ///     type Item = u32;
+    ///
+    ///     async fn next(&mut self) -> Option<u32> {
+    ///         self.0 += 1;
+    ///         Some(self.0)
+    ///     }
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let mut naturals = Naturals(0);
+    /// let mut items = vec![];
+    /// (&mut naturals).take(3).for_each(async |n| items.push(n)).await;
+    /// assert_eq!(items, [1, 2, 3]);
+    /// assert_eq!(naturals.0, 3);
+    ///
+    /// let mut none = (&mut naturals).take(0);
+    /// assert_eq!(none.next().await, None);
+    /// drop(none);
+    /// assert_eq!(naturals.0, 3);
+    /// # });
+    /// ```
+    fn take(self, n: usize) -> impl AsyncIterator<Item = Self::Item>
+    where
+        Self: Sized,
+    {
+        Take {
+            iter: self,
+            remaining: n,
+        }
+    }
+
+    /// Skips the first `n` items, then yields the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut items = vec![];
+    /// moro::from_iter(1..=5).skip(2).for_each(async |n| items.push(n)).await;
+    /// assert_eq!(items, [3, 4, 5]);
+    /// # });
+    /// ```
+    fn skip(self, n: usize) -> impl AsyncIterator<Item = Self::Item>
+    where
+        Self: Sized,
+    {
+        Skip {
+            iter: self,
+            remaining: n,
+        }
+    }
+
+    /// Pairs each item with its zero-based position, like
+    /// [`Iterator::enumerate`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut survivors = vec![];
+    /// moro::from_iter(["a", "bb", "ccc", "dd"])
+    ///     .enumerate()
+    ///     .filter(async |(_, s)| s.len() == 2)
+    ///     .for_each(async |(i, _)| survivors.push(i))
+    ///     .await;
+    /// assert_eq!(survivors, [1, 3]);
+    /// # });
+    /// ```
+    fn enumerate(self) -> impl AsyncIterator<Item = (usize, Self::Item)>
+    where
+        Self: Sized,
+    {
+        Enumerate {
+            iter: self,
+            count: 0,
+        }
+    }
+
+    /// Yields pairs of items from `self` and `other`, ending as soon as
+    /// either is exhausted; like [`Iterator::zip`].
+    ///
+    /// The two `next` calls are awaited concurrently, so a slow producer on
+    /// one side overlaps with the other. When one side ends, the item the
+    /// other side produced in the same step is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let evens = moro::from_iter(1..=10).filter(async |n| n % 2 == 0);
+    /// let mut pairs = vec![];
+    /// evens
+    ///     .zip(moro::from_iter(["a", "b", "c"]))
+    ///     .for_each(async |pair| pairs.push(pair))
+    ///     .await;
+    /// assert_eq!(pairs, [(2, "a"), (4, "b"), (6, "c")]);
+    /// # });
+    /// ```
+    fn zip<J: AsyncIterator>(self, other: J) -> impl AsyncIterator<Item = (Self::Item, J::Item)>
+    where
+        Self: Sized,
+    {
+        Zip { a: self, b: other }
+    }
+
+    /// Calls `op` on each item in turn, waiting for it to finish before
+    /// fetching the next item; like [`StreamExt::for_each`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut seen = vec![];
+    /// moro::from_iter(1..=6)
+    ///     .filter(async |n| n % 2 == 0)
+    ///     .for_each(async |n| seen.push(n * 10))
+    ///     .await;
+    /// assert_eq!(seen, [20, 40, 60]);
+    /// # });
+    /// ```
+    async fn for_each(mut self, mut op: impl async FnMut(Self::Item))
+    where
+        Self: Sized,
+    {
+        while let Some(item) = self.next().await {
+            op(item).await;
+        }
+    }
+
+    /// Folds every item into an accumulator, starting from `init`; like
+    /// [`Iterator::fold`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let sum = moro::from_iter(1..=6)
+    ///     .filter(async |n| n % 2 == 0)
+    ///     .fold(0, async |sum, n| sum + n)
+    ///     .await;
+    /// assert_eq!(sum, 12);
+    /// # });
+    /// ```
+    async fn fold<R>(mut self, init: R, mut op: impl async FnMut(R, Self::Item) -> R) -> R
+    where
+        Self: Sized,
+    {
+        let mut acc = init;
+        while let Some(item) = self.next().await {
+            acc = op(acc, item).await;
+        }
+        acc
+    }
+
+    /// Consumes the iterator, returning the number of items it yielded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let evens = moro::from_iter(1..=7).filter(async |n| n % 2 == 0).count().await;
+    /// assert_eq!(evens, 3);
+    /// # });
+    /// ```
+    async fn count(mut self) -> usize
+    where
+        Self: Sized,
+    {
+        let mut count = 0;
+        while self.next().await.is_some() {
+            count += 1;
+        }
+        count
+    }
+
+    /// Returns the first item for which `op` returns true, leaving the
+    /// iterator positioned after it; like [`Iterator::find`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut iter = moro::from_iter([1, 4, 7, 10]);
+    /// assert_eq!(iter.find(async |n| n % 2 == 0).await, Some(4));
+    /// assert_eq!(iter.next().await, Some(7));
+    /// assert_eq!(iter.find(async |n| *n > 10).await, None);
+    /// # });
+    /// ```
+    async fn find(&mut self, mut op: impl async FnMut(&Self::Item) -> bool) -> Option<Self::Item> {
+        while let Some(item) = self.next().await {
+            if op(&item).await {
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    /// Returns true if `op` returns true for any item, stopping at the first
+    /// such item; like [`Iterator::any`]. Returns false for an empty
+    /// iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut iter = moro::from_iter([1, 4, 7]);
+    /// assert!(iter.any(async |n| n > 3).await);
+    /// assert_eq!(iter.next().await, Some(7));
+    /// # });
+    /// ```
+    async fn any(&mut self, mut op: impl async FnMut(Self::Item) -> bool) -> bool {
+        while let Some(item) = self.next().await {
+            if op(item).await {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns true if `op` returns true for every item, stopping at the
+    /// first item for which it returns false; like [`Iterator::all`].
+    /// Returns true for an empty iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut iter = moro::from_iter([2, 4, 5, 6]);
+    /// assert!(!iter.all(async |n| n % 2 == 0).await);
+    /// assert_eq!(iter.next().await, Some(6));
+    /// # });
+    /// ```
+    async fn all(&mut self, mut op: impl async FnMut(Self::Item) -> bool) -> bool {
+        while let Some(item) = self.next().await {
+            if !op(item).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Runs `op` on up to `limit` items concurrently; like
+    /// [`StreamExt::for_each_concurrent`].
+    ///
+    /// `op` is an ordinary `FnMut` returning a future rather than an
+    /// `async FnMut`: it is called once per item, in turn, and the futures it
+    /// returns run concurrently, so they cannot borrow from `op` itself.
+    /// Data shared between items is captured by reference or cloned into
+    /// each future. Fetching further items from `self` runs concurrently with
+    /// the items in flight.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::Mutex;
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let seen = Mutex::new(vec![]);
+    /// moro::from_iter(1..=4)
+    ///     .for_each_concurrent(2, |n| {
+    ///         let seen = &seen;
+    ///         async move { seen.lock().unwrap().push(n * 10) }
+    ///     })
+    ///     .await;
+    /// let mut seen = seen.into_inner().unwrap();
+    /// seen.sort();
+    /// assert_eq!(seen, [10, 20, 30, 40]);
+    /// # });
+    /// ```
+    ///
+    /// An item in flight can thus produce the next one:
+    ///
+    /// ```rust
+    /// # use std::sync::Mutex;
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let (tx, rx) = futures::channel::oneshot::channel();
+    /// let (tx, mut rx) = (Mutex::new(Some(tx)), Some(rx));
+    /// moro::from_iter([1, 2])
+    ///     .map(async |n| {
+    ///         if n == 2 {
+    ///             // Waits for item 1 to be processed.
+    ///             rx.take().unwrap().await.unwrap();
+    ///         }
+    ///         n
+    ///     })
+    ///     .for_each_concurrent(2, |n| {
+    ///         let tx = &tx;
+    ///         async move {
+    ///             if n == 1 {
+    ///                 tx.lock().unwrap().take().unwrap().send(()).unwrap();
+    ///             }
+    ///         }
+    ///     })
+    ///     .await;
+    /// # });
+    /// ```
+    async fn for_each_concurrent<F>(self, limit: usize, mut op: impl FnMut(Self::Item) -> F)
+    where
+        Self: Sized,
+        F: Future<Output = ()>,
+    {
+        assert!(limit > 0, "concurrency limit must be positive");
+        let mut source = std::pin::pin!(fused_stream(self));
+        let mut in_flight = FuturesUnordered::new();
+        std::future::poll_fn(|cx| loop {
+            let room = limit - in_flight.len();
+            fill(cx, source.as_mut(), room, |item| in_flight.push(op(item)));
+            match in_flight.poll_next_unpin(cx) {
+                Poll::Ready(Some(())) => {}
+                Poll::Ready(None) if source.is_terminated() => return Poll::Ready(()),
+                Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Runs `op` on up to `limit` items concurrently, then passes each result
+    /// to `commit` *in input order*.
+    ///
+    /// This is useful when the per-item work can run concurrently but its
+    /// side effect must be applied in order, e.g. appending to a log. Results
+    /// that complete out of order are buffered until all earlier ones have
+    /// been committed. Beware of head-of-line blocking: buffered results
+    /// count against `limit`, so a single slow item stalls progress until it
+    /// completes. Fetching further items from `self` runs concurrently with
+    /// the items in flight, but while `commit` runs, no other work makes
+    /// progress.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::Mutex;
+    /// # use moro::AsyncIterator;
+    /// # use moro::test_util::yield_now;
+    /// # futures::executor::block_on(async {
+    /// let finished = Mutex::new(vec![]);
+    /// let mut committed = vec![];
+    /// moro::from_iter([3, 1, 2])
+    ///     .for_each_concurrent_ordered(
+    ///         3,
+    ///         |n| {
+    ///             let finished = &finished;
+    ///             async move {
+    ///                 for _ in 0..n {
+    ///                     yield_now().await;
+    ///                 }
+    ///                 finished.lock().unwrap().push(n);
+    ///                 n
+    ///             }
+    ///         },
+    ///         async |n| committed.push(n),
+    ///     )
+    ///     .await;
+    /// assert_eq!(finished.into_inner().unwrap(), [1, 2, 3]);
+    /// assert_eq!(committed, [3, 1, 2]);
+    /// # });
+    /// ```
+    async fn for_each_concurrent_ordered<U, F>(
+        self,
+        limit: usize,
+        mut op: impl FnMut(Self::Item) -> F,
+        mut commit: impl async FnMut(U),
+    ) where
+        Self: Sized,
+        F: Future<Output = U>,
+    {
+        assert!(limit > 0, "concurrency limit must be positive");
+        let mut source = std::pin::pin!(fused_stream(self));
+        let mut in_flight = FuturesOrdered::new();
+        loop {
+            let next = std::future::poll_fn(|cx| {
+                let room = limit - in_flight.len();
+                fill(cx, source.as_mut(), room, |item| {
+                    in_flight.push_back(op(item))
+                });
+                match in_flight.poll_next_unpin(cx) {
+                    Poll::Ready(None) if !source.is_terminated() => Poll::Pending,
+                    poll => poll,
+                }
+            })
+            .await;
+            match next {
+                Some(result) => commit(result).await,
+                None => break,
+            }
+        }
+    }
+
+    /// Spawns the futures yielded by `self` into `scope`, running up to
+    /// `limit` of them at a time, and yields their outputs as they complete;
+    /// like [`StreamExt::buffer_unordered`].
+    ///
+    /// Futures are only pulled from `self` when the iterator is polled and
+    /// fewer than `limit` of them are in flight. The jobs belong to `scope`,
+    /// so if the scope is cancelled, the futures still in flight are dropped
+    /// with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let sizes = moro::scope(async |scope| {
+    ///     let mut sizes = vec![];
+    ///     moro::from_iter(["a.txt", "bb.txt", "ccc.txt"])
+    ///         .map(async |path: &str| async move { path.len() })
+    ///         .buffer_unordered(scope, 2)
+    ///         .for_each(async |size| sizes.push(size))
+    ///         .await;
+    ///     sizes.sort();
+    ///     sizes
+    /// })
+    /// .await;
+    /// assert_eq!(sizes, [5, 6, 7]);
+    /// # });
+    /// ```
+    fn buffer_unordered<'scope, 'env, R, T>(
+        self,
+        scope: &'scope Scope<'scope, 'env, R>,
+        limit: usize,
+    ) -> impl AsyncIterator<Item = T>
+    where
+        Self: Sized,
+        Self::Item: Future<Output = T> + Send + 'scope,
+        R: Send + 'env,
+        T: Send + 'scope,
+    {
+        assert!(limit > 0, "concurrency limit must be positive");
+        BufferUnordered {
+            iter: self,
+            jobs: scope.job_set(),
+            limit,
+            exhausted: false,
+        }
+    }
+
+    /// Yields all items of `self`, followed by all items of `other`; like
+    /// [`Iterator::chain`].
+    ///
+    /// `other` is not polled until `self` is exhausted, and `self` is
+    /// dropped at that point.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let urgent = moro::from_iter(["fix build", "deploy"]);
+    /// let backlog = moro::from_iter(["docs", "tests", "dashboards"]);
+    /// let mut work = vec![];
+    /// urgent
+    ///     .chain(backlog)
+    ///     .filter(async |task| task.starts_with('d'))
+    ///     .for_each(async |task| work.push(task))
+    ///     .await;
+    /// assert_eq!(work, ["deploy", "docs", "dashboards"]);
+    /// # });
+    /// ```
+    fn chain<J>(self, other: J) -> impl AsyncIterator<Item = Self::Item>
+    where
+        Self: Sized,
+        J: AsyncIterator<Item = Self::Item>,
+    {
+        Chain {
+            first: Some(self),
+            second: other,
+        }
+    }
+
+    /// Yields all items of `self`, followed by all items of `stream`.
+    ///
+    /// `stream` is pinned internally, so it need not be `Unpin`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let mut iter = moro::from_iter(vec![1, 2]).chain_stream(futures::stream::iter(vec![3, 4]));
+    /// let mut items = vec![];
+    /// while let Some(item) = iter.next().await {
+    ///     items.push(item);
+    /// }
+    /// assert_eq!(items, [1, 2, 3, 4]);
+    /// # });
+    /// ```
+    fn chain_stream<S>(self, stream: S) -> impl AsyncIterator<Item = Self::Item>
+    where
+        Self: Sized,
+        S: futures::Stream<Item = Self::Item>,
+    {
+        ChainStream {
+            iter: Some(self),
+            stream: Box::pin(stream),
+        }
+    }
+}
+
+impl<I: AsyncIterator + ?Sized> AsyncIterator for &mut I {
+    type Item = I::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        I::next(self).await
+    }
+}
+
+/// Owns `iter` and yields its items, like [`into_stream`], but keeps
+/// returning `None` once exhausted.
+fn fused_stream<I: AsyncIterator>(
+    iter: I,
+) -> futures::stream::Fuse<impl futures::Stream<Item = I::Item>> {
+    futures::stream::unfold(iter, async |mut iter| {
+        let item = iter.next().await?;
+        Some((item, iter))
+    })
+    .fuse()
+}
+
+/// Passes up to `room` items that `source` has ready to `push`.
+fn fill<S: futures::Stream>(
+    cx: &mut Context<'_>,
+    mut source: Pin<&mut S>,
+    room: usize,
+    mut push: impl FnMut(S::Item),
+) {
+    for _ in 0..room {
+        match source.as_mut().poll_next(cx) {
+            Poll::Ready(Some(item)) => push(item),
+            Poll::Ready(None) | Poll::Pending => break,
+        }
+    }
+}
+
+/// Creates an [`AsyncIterator`] that yields the items of `iter`.
+pub fn from_iter<I: IntoIterator>(iter: I) -> impl AsyncIterator<Item = I::Item> {
+    FromIter {
+        iter: iter.into_iter(),
+    }
+}
+
+/// Creates an [`AsyncIterator`] that yields the items of `stream`.
+///
+/// `stream` is pinned on the heap internally, so it need not be `Unpin`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use moro::AsyncIterator;
+/// # futures::executor::block_on(async {
+/// let (tx, rx) = futures::channel::mpsc::unbounded();
+/// for i in 1..=4 {
+///     tx.unbounded_send(i).unwrap();
+/// }
+/// drop(tx);
+/// let sum = moro::from_stream(rx)
+///     .filter(async |n| n % 2 == 0)
+///     .fold(0, async |sum, n| sum + n)
+///     .await;
+/// assert_eq!(sum, 6);
+/// # });
+/// ```
+pub fn from_stream<S: futures::Stream>(stream: S) -> impl AsyncIterator<Item = S::Item> {
+    FromStream {
+        stream: Box::pin(stream),
+    }
+}
+
+/// Creates a [`Stream`](futures::Stream) that yields the items of `iter`,
+/// the reverse of [`from_stream`].
+///
+/// The stream owns `iter` and drives one call to
+/// [`next`][AsyncIterator::next] at a time. It is not `Unpin`; pin it with
+/// [`std::pin::pin!`] or [`Box::pin`] where required.
+///
+/// # Examples
+///
+/// ```rust
+/// # use futures::StreamExt;
+/// # use moro::AsyncIterator;
+/// # futures::executor::block_on(async {
+/// let iter = moro::from_iter(1..=5).filter(async |n| n % 2 == 1);
+/// let items: Vec<_> = moro::into_stream(iter).map(|n| n * 10).collect().await;
+/// assert_eq!(items, [10, 30, 50]);
+/// # });
+/// ```
+pub fn into_stream<I: AsyncIterator>(iter: I) -> impl futures::Stream<Item = I::Item> {
+    futures::stream::unfold(iter, async |mut iter| {
+        let item = iter.next().await?;
+        Some((item, iter))
+    })
+}
+
+struct FromStream<S> {
+    stream: std::pin::Pin<Box<S>>,
+}
+
+impl<S: futures::Stream> AsyncIterator for FromStream<S> {
+    type Item = S::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        self.stream.next().await
+    }
+}
+
+struct FromIter<I> {
+    iter: I,
+}
+
+impl<I: Iterator> AsyncIterator for FromIter<I> {
+    type Item = I::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
 }
 
 pub trait IntoAsyncIter {
@@ -50,6 +886,61 @@ impl<T: AsyncIterator> IntoAsyncIter for T {
     }
 }
 
+/// Yields the elements of the vector in order.
+///
+/// # Examples
+///
+/// ```rust
+/// # use moro::{AsyncIterator, IntoAsyncIter};
+/// # futures::executor::block_on(async {
+/// let doubled = moro::scope(async |scope| {
+///     let mut doubled = vec![];
+///     vec![1, 2, 3, 4]
+///         .into_async_iter(scope)
+///         .filter(async |n| n % 2 == 0)
+///         .for_each(async |n| doubled.push(n * 2))
+///         .await;
+///     doubled
+/// })
+/// .await;
+/// assert_eq!(doubled, [4, 8]);
+/// # });
+/// ```
+impl<T> IntoAsyncIter for Vec<T> {
+    type Item = T;
+
+    fn into_async_iter<R: Send>(
+        self,
+        _scope: &Scope<'_, '_, R>,
+    ) -> impl AsyncIterator<Item = Self::Item> {
+        from_iter(self)
+    }
+}
+
+/// Yields the elements of the array in order.
+impl<T, const N: usize> IntoAsyncIter for [T; N] {
+    type Item = T;
+
+    fn into_async_iter<R: Send>(
+        self,
+        _scope: &Scope<'_, '_, R>,
+    ) -> impl AsyncIterator<Item = Self::Item> {
+        from_iter(self)
+    }
+}
+
+/// Yields references to the elements of the slice in order.
+impl<'a, T> IntoAsyncIter for &'a [T] {
+    type Item = &'a T;
+
+    fn into_async_iter<R: Send>(
+        self,
+        _scope: &Scope<'_, '_, R>,
+    ) -> impl AsyncIterator<Item = Self::Item> {
+        from_iter(self)
+    }
+}
+
 struct Filter<I, O>
 where
     I: AsyncIterator,
@@ -75,3 +966,283 @@ where
         }
     }
 }
+
+struct Map<I, O> {
+    iter: I,
+    map_op: O,
+}
+
+impl<I, O, U> AsyncIterator for Map<I, O>
+where
+    I: AsyncIterator,
+    O: async FnMut(I::Item) -> U,
+{
+    type Item = U;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next().await?;
+        Some((self.map_op)(item).await)
+    }
+}
+
+struct FlatMap<I, O, J> {
+    iter: I,
+    map_op: O,
+
+    /// The iterator of the current item of `iter`, if not yet exhausted.
+    inner: Option<J>,
+}
+
+impl<I, O, J> AsyncIterator for FlatMap<I, O, J>
+where
+    I: AsyncIterator,
+    O: async FnMut(I::Item) -> J,
+    J: AsyncIterator,
+{
+    type Item = J::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(inner) = &mut self.inner {
+                if let Some(item) = inner.next().await {
+                    return Some(item);
+                }
+                self.inner = None;
+            }
+            let item = self.iter.next().await?;
+            self.inner = Some((self.map_op)(item).await);
+        }
+    }
+}
+
+struct BufferUnordered<'scope, 'env, I, R, T>
+where
+    R: Send + 'env,
+{
+    iter: I,
+    jobs: JobSet<'scope, 'env, R, T>,
+    limit: usize,
+
+    /// Whether `iter` has returned `None`.
+    exhausted: bool,
+}
+
+impl<'scope, 'env, I, R, T> AsyncIterator for BufferUnordered<'scope, 'env, I, R, T>
+where
+    I: AsyncIterator,
+    I::Item: Future<Output = T> + Send + 'scope,
+    R: Send + 'env,
+    T: Send + 'scope,
+{
+    type Item = T;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        while !self.exhausted && self.jobs.len() < self.limit {
+            match self.iter.next().await {
+                Some(future) => self.jobs.spawn(future),
+                None => self.exhausted = true,
+            }
+        }
+        self.jobs.next().await
+    }
+}
+
+struct Take<I> {
+    iter: I,
+    remaining: usize,
+}
+
+impl<I: AsyncIterator> AsyncIterator for Take<I> {
+    type Item = I::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.iter.next().await
+    }
+}
+
+struct Skip<I> {
+    iter: I,
+    remaining: usize,
+}
+
+impl<I: AsyncIterator> AsyncIterator for Skip<I> {
+    type Item = I::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            self.remaining -= 1;
+            self.iter.next().await?;
+        }
+        self.iter.next().await
+    }
+}
+
+struct Enumerate<I> {
+    iter: I,
+    count: usize,
+}
+
+impl<I: AsyncIterator> AsyncIterator for Enumerate<I> {
+    type Item = (usize, I::Item);
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next().await?;
+        let index = self.count;
+        self.count += 1;
+        Some((index, item))
+    }
+}
+
+struct Zip<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: AsyncIterator, B: AsyncIterator> AsyncIterator for Zip<A, B> {
+    type Item = (A::Item, B::Item);
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        match futures::join!(self.a.next(), self.b.next()) {
+            (Some(a), Some(b)) => Some((a, b)),
+            _ => None,
+        }
+    }
+}
+
+struct TryFilter<I, O> {
+    iter: I,
+    filter_op: O,
+}
+
+impl<I, O, T, E> AsyncIterator for TryFilter<I, O>
+where
+    I: AsyncIterator<Item = Result<T, E>>,
+    O: async FnMut(&T) -> Result<bool, E>,
+{
+    type Item = Result<T, E>;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = match self.iter.next().await? {
+                Ok(item) => item,
+                Err(e) => return Some(Err(e)),
+            };
+            match (self.filter_op)(&item).await {
+                Ok(true) => return Some(Ok(item)),
+                Ok(false) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+struct TakeWhile<I, O>
+where
+    I: AsyncIterator,
+    O: async FnMut(&I::Item) -> bool,
+{
+    iter: I,
+    take_op: O,
+    done_taking: bool,
+}
+
+impl<I, O> AsyncIterator for TakeWhile<I, O>
+where
+    I: AsyncIterator,
+    O: async FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        if self.done_taking {
+            return None;
+        }
+        let item = self.iter.next().await?;
+        if (self.take_op)(&item).await {
+            Some(item)
+        } else {
+            self.done_taking = true;
+            None
+        }
+    }
+}
+
+struct SkipWhile<I, O>
+where
+    I: AsyncIterator,
+    O: async FnMut(&I::Item) -> bool,
+{
+    iter: I,
+    skip_op: O,
+    done_skipping: bool,
+}
+
+impl<I, O> AsyncIterator for SkipWhile<I, O>
+where
+    I: AsyncIterator,
+    O: async FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = self.iter.next().await?;
+            if self.done_skipping || !(self.skip_op)(&item).await {
+                self.done_skipping = true;
+                return Some(item);
+            }
+        }
+    }
+}
+
+struct Chain<A, B> {
+    /// Set to `None` once exhausted.
+    first: Option<A>,
+    second: B,
+}
+
+impl<A, B> AsyncIterator for Chain<A, B>
+where
+    A: AsyncIterator,
+    B: AsyncIterator<Item = A::Item>,
+{
+    type Item = A::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        if let Some(first) = &mut self.first {
+            if let Some(item) = first.next().await {
+                return Some(item);
+            }
+            self.first = None;
+        }
+        self.second.next().await
+    }
+}
+
+struct ChainStream<I, S> {
+    /// Set to `None` once exhausted.
+    iter: Option<I>,
+    stream: std::pin::Pin<Box<S>>,
+}
+
+impl<I, S> AsyncIterator for ChainStream<I, S>
+where
+    I: AsyncIterator,
+    S: futures::Stream<Item = I::Item>,
+{
+    type Item = I::Item;
+
+    async fn next(&mut self) -> Option<Self::Item> {
+        if let Some(iter) = &mut self.iter {
+            if let Some(item) = iter.next().await {
+                return Some(item);
+            }
+            self.iter = None;
+        }
+        futures::StreamExt::next(&mut self.stream).await
+    }
+}
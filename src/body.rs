@@ -1,6 +1,6 @@
 use std::{pin::Pin, sync::Arc, task::Poll};
 
-use futures::{Future, FutureExt};
+use futures::Future;
 use pin_project::{pin_project, pinned_drop};
 
 use crate::scope::Scope;
@@ -10,6 +10,9 @@ use crate::scope::Scope;
 /// It is not considered complete until (a) the body is done and (b) any spawned futures are done.
 /// Its result is whatever the body returned.
 ///
+/// The body usually returns the scope's result type `R`, but a local scope's
+/// body may return something else, see [`Body::poll_with`].
+///
 /// # Unsafe contract
 ///
 /// - `body_future` and `result` will be dropped BEFORE `scope`.
@@ -18,16 +21,18 @@ pub(crate) struct Body<'scope, 'env: 'scope, R, F>
 where
     R: Send,
     R: 'env,
+    F: Future,
 {
     #[pin]
     body_future: Option<F>,
-    result: Option<R>,
+    result: Option<F::Output>,
     scope: Arc<Scope<'scope, 'env, R>>,
 }
 
 impl<'scope, 'env, R, F> Body<'scope, 'env, R, F>
 where
     R: Send,
+    F: Future,
 {
     /// # Unsafe contract
     ///
@@ -40,18 +45,87 @@ where
         }
     }
 
+    pub(crate) fn scope(&self) -> &Scope<'scope, 'env, R> {
+        &self.scope
+    }
+
     fn clear(self: Pin<&mut Self>) {
         let mut this = self.project();
         this.body_future.set(None);
         this.result.take();
         this.scope.clear();
     }
+
+    /// Polls the body and the jobs; if the scope was terminated with `v`,
+    /// the result is `terminated(v)`.
+    pub(crate) fn poll_with(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        terminated: impl FnOnce(R) -> F::Output,
+    ) -> Poll<F::Output> {
+        let mut this = self.project();
+
+        // If the body is not yet finished, poll that. Once it becomes finished,
+        // we will update `this.result.
+        //
+        // A self-limiting scope holds back the body while too many jobs are
+        // in flight.
+        let mut held_back = false;
+        if this.scope.is_terminated() {
+            // Graceful jobs may still be shutting down, but the body is done.
+            this.body_future.set(None);
+        }
+        if let Some(body_future) = this.body_future.as_mut().as_pin_mut() {
+            held_back = !this.scope.admits_body();
+            if !held_back {
+                match body_future.poll(cx) {
+                    Poll::Ready(r) => {
+                        *this.result = Some(r);
+                        this.body_future.set(None);
+                        if this.scope.detaches_remaining() {
+                            this.scope.clear();
+                        }
+                    }
+                    Poll::Pending => {}
+                }
+            }
+        }
+
+        // Check if the scope is ready.
+        //
+        // If polling the scope returns `Some`, then the scope was early terminated,
+        // so forward that result. Otherwise, the `result` from our body future
+        // should be available, so return that.
+        let jobs = this.scope.poll_jobs(cx);
+        if held_back && this.scope.admits_body() {
+            // Jobs completed while we polled them, making room for the body;
+            // nothing else would wake us up to poll it.
+            cx.waker().wake_by_ref();
+        }
+        let result = match ready!(jobs) {
+            Some(v) => {
+                // Drop the body and the jobs right away, rather than when we
+                // are dropped, so that whatever they hold (e.g. locks) is
+                // released even if we are kept around.
+                this.body_future.set(None);
+                this.scope.clear();
+                terminated(v)
+            }
+            None => match this.result.take() {
+                None => return Poll::Pending,
+                Some(v) => v,
+            },
+        };
+        this.scope.set_complete();
+        Poll::Ready(result)
+    }
 }
 
 #[pinned_drop]
 impl<'scope, 'env, R, F> PinnedDrop for Body<'scope, 'env, R, F>
 where
     R: Send,
+    F: Future,
 {
     fn drop(self: Pin<&mut Self>) {
         // Fulfill our unsafe contract and ensure we drop other fields
@@ -68,31 +142,6 @@ where
     type Output = R;
 
     fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
-        let mut this = self.project();
-
-        // If the body is not yet finished, poll that. Once it becomes finished,
-        // we will update `this.result.
-        if let Some(body_future) = this.body_future.as_mut().as_pin_mut() {
-            match body_future.poll(cx) {
-                Poll::Ready(r) => {
-                    *this.result = Some(r);
-                    this.body_future.set(None);
-                }
-                Poll::Pending => {}
-            }
-        }
-
-        // Check if the scope is ready.
-        //
-        // If polling the scope returns `Some`, then the scope was early terminated,
-        // so forward that result. Otherwise, the `result` from our body future
-        // should be available, so return that.
-        match ready!(this.scope.poll_jobs(cx)) {
-            Some(v) => return Poll::Ready(v),
-            None => match this.result.take() {
-                None => Poll::Pending,
-                Some(v) => Poll::Ready(v),
-            },
-        }
+        self.poll_with(cx, |v| v)
     }
 }
@@ -0,0 +1,34 @@
+use std::marker::PhantomData;
+
+/// Token passed to the closure of
+/// [`Scope::spawn_borrowing`][crate::Scope::spawn_borrowing], through which a
+/// job explicitly borrows data from the environment of the scope.
+///
+/// Data borrowed through the token must outlive the whole scope (`'env`), and
+/// if it doesn't, the compiler reports it at the call to
+/// [`share`][Self::share] or [`exclusive`][Self::exclusive] naming the
+/// offending variable, rather than at the spawn.
+#[derive(Copy, Clone)]
+pub struct Borrows<'env> {
+    /// Invariant, so that `'env` cannot be shortened to fit a borrow.
+    phantom: PhantomData<fn(&'env ()) -> &'env ()>,
+}
+
+impl<'env> Borrows<'env> {
+    pub(crate) fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+
+    /// Borrows `data`, which lives outside the scope, for use by the job.
+    pub fn share<T: ?Sized>(self, data: &'env T) -> &'env T {
+        data
+    }
+
+    /// Borrows `data`, which lives outside the scope, mutably for use by
+    /// the job. No other job can then access `data`.
+    pub fn exclusive<T: ?Sized>(self, data: &'env mut T) -> &'env mut T {
+        data
+    }
+}
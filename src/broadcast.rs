@@ -0,0 +1,152 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, Weak},
+    task::{Poll, Waker},
+};
+
+use crate::AsyncIterator;
+
+/// Sending half of a broadcast channel, see [`Scope::broadcast`][crate::Scope::broadcast].
+///
+/// Dropping the sender closes the channel: receivers yield the messages they
+/// have buffered and then `None`.
+pub struct BroadcastSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Receiving half of a broadcast channel, see [`Scope::broadcast`][crate::Scope::broadcast].
+///
+/// Implements [`AsyncIterator`], yielding every message sent while the
+/// receiver exists, except those dropped because the receiver lagged.
+pub struct BroadcastReceiver<T> {
+    slot: Arc<LockedSlot<T>>,
+}
+
+struct Shared<T> {
+    capacity: usize,
+
+    /// The current receivers; `None` once the sender was dropped.
+    receivers: Mutex<Option<Vec<Weak<LockedSlot<T>>>>>,
+}
+
+type LockedSlot<T> = Mutex<Slot<T>>;
+
+/// Buffer of a single receiver.
+struct Slot<T> {
+    buffer: VecDeque<T>,
+    missed: u64,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+pub(crate) fn channel<T>(
+    capacity: usize,
+) -> (
+    BroadcastSender<T>,
+    impl Fn() -> BroadcastReceiver<T> + Clone + Send + Sync,
+)
+where
+    T: Clone + Send,
+{
+    assert!(capacity > 0, "broadcast capacity must be positive");
+    let shared = Arc::new(Shared {
+        capacity,
+        receivers: Mutex::new(Some(vec![])),
+    });
+    let subscribe = {
+        let shared = shared.clone();
+        move || {
+            let mut receivers = shared.receivers.lock().unwrap();
+            let slot = Arc::new(Mutex::new(Slot {
+                buffer: VecDeque::with_capacity(shared.capacity),
+                missed: 0,
+                closed: receivers.is_none(),
+                waker: None,
+            }));
+            if let Some(receivers) = &mut *receivers {
+                receivers.push(Arc::downgrade(&slot));
+            }
+            BroadcastReceiver { slot }
+        }
+    };
+    (BroadcastSender { shared }, subscribe)
+}
+
+impl<T: Clone> BroadcastSender<T> {
+    /// Sends `value` to every current receiver. Never waits: a receiver
+    /// whose buffer is full drops its oldest message to make room.
+    pub fn send(&self, value: T) {
+        let mut receivers = self.shared.receivers.lock().unwrap();
+        // Only the sender closes the channel, so it is still open.
+        let receivers = receivers.as_mut().unwrap();
+        receivers.retain(|slot| {
+            let Some(slot) = slot.upgrade() else {
+                return false;
+            };
+            let mut slot = slot.lock().unwrap();
+            if slot.buffer.len() == self.shared.capacity {
+                slot.buffer.pop_front();
+                slot.missed += 1;
+            }
+            slot.buffer.push_back(value.clone());
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+            true
+        });
+    }
+
+    /// Number of receivers that currently exist.
+    pub fn receiver_count(&self) -> usize {
+        let receivers = self.shared.receivers.lock().unwrap();
+        let receivers = receivers.as_ref().unwrap();
+        receivers.iter().filter(|r| r.strong_count() > 0).count()
+    }
+}
+
+impl<T> Drop for BroadcastSender<T> {
+    fn drop(&mut self) {
+        let receivers = self.shared.receivers.lock().unwrap().take();
+        for slot in receivers.into_iter().flatten() {
+            if let Some(slot) = slot.upgrade() {
+                let mut slot = slot.lock().unwrap();
+                slot.closed = true;
+                if let Some(waker) = slot.waker.take() {
+                    waker.wake();
+                }
+            }
+        }
+    }
+}
+
+impl<T> BroadcastReceiver<T> {
+    /// Receives the next message, or `None` once the sender was dropped
+    /// and all buffered messages have been received.
+    pub async fn recv(&mut self) -> Option<T> {
+        std::future::poll_fn(|cx| {
+            let mut slot = self.slot.lock().unwrap();
+            if let Some(value) = slot.buffer.pop_front() {
+                Poll::Ready(Some(value))
+            } else if slot.closed {
+                Poll::Ready(None)
+            } else {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Number of messages this receiver missed because its buffer was full.
+    pub fn missed(&self) -> u64 {
+        self.slot.lock().unwrap().missed
+    }
+}
+
+impl<T> AsyncIterator for BroadcastReceiver<T> {
+    type Item = T;
+
+    async fn next(&mut self) -> Option<T> {
+        self.recv().await
+    }
+}
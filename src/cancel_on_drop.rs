@@ -0,0 +1,100 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{channel::oneshot, future::AbortHandle, Future};
+
+/// Handle to a job spawned with
+/// [`Scope::spawn_cancel_on_drop`][crate::Scope::spawn_cancel_on_drop];
+/// awaiting it yields the job's result.
+///
+/// Unlike [`Spawned`][crate::Spawned], dropping this handle cancels the job:
+/// the job is dropped the next time the scope polls it, i.e. at its next
+/// `.await`. This matches the task handles of e.g. `smol`, whereas a job
+/// spawned with [`Scope::spawn`][crate::Scope::spawn] keeps running when its
+/// handle is dropped, until it completes or the scope is terminated.
+pub struct CancelOnDrop<T> {
+    rx: oneshot::Receiver<T>,
+
+    /// Aborts the job on drop; `None` once the job was detached.
+    abort: Option<AbortHandle>,
+}
+
+impl<T> CancelOnDrop<T> {
+    pub(crate) fn new(rx: oneshot::Receiver<T>, abort: AbortHandle) -> Self {
+        Self {
+            rx,
+            abort: Some(abort),
+        }
+    }
+
+    /// Lets the job run to completion, like a job spawned with
+    /// [`Scope::spawn`][crate::Scope::spawn] whose handle was dropped.
+    ///
+    /// The handle is consumed, so the job's result is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// struct Guard<'a>(&'a AtomicBool);
+    ///
+    /// impl Drop for Guard<'_> {
+    ///     fn drop(&mut self) {
+    ///         self.0.store(true, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let (dropped, finished) = (AtomicBool::new(false), AtomicBool::new(false));
+    /// moro::async_scope!(|scope| {
+    ///     let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    ///     let rx = futures::FutureExt::shared(rx);
+    ///     let cancelled = scope.spawn_cancel_on_drop({
+    ///         let rx = rx.clone();
+    ///         let dropped = &dropped;
+    ///         async move {
+    ///             let _guard = Guard(dropped);
+    ///             let _ = rx.await;
+    ///         }
+    ///     });
+    ///     let detached = scope.spawn_cancel_on_drop(async {
+    ///         let _ = rx.await;
+    ///         finished.store(true, Ordering::SeqCst);
+    ///     });
+    ///     // Awaiting another job lets the scope start both jobs.
+    ///     scope.spawn(async {}).await;
+    ///     drop(cancelled);
+    ///     detached.detach();
+    ///     scope.spawn(async {}).await;
+    ///     assert!(dropped.load(Ordering::SeqCst));
+    ///     tx.send(()).unwrap();
+    /// })
+    /// .await;
+    /// assert!(finished.load(Ordering::SeqCst));
+    /// # });
+    /// ```
+    pub fn detach(mut self) {
+        self.abort = None;
+    }
+}
+
+impl<T> Future for CancelOnDrop<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        Pin::new(&mut self.rx).poll(cx).map(|r| match r {
+            Ok(v) => v,
+            Err(e) => panic!("unexpected error: {e:?}"),
+        })
+    }
+}
+
+impl<T> Drop for CancelOnDrop<T> {
+    fn drop(&mut self) {
+        if let Some(abort) = &self.abort {
+            abort.abort();
+        }
+    }
+}
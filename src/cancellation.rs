@@ -0,0 +1,85 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Poll, Waker},
+};
+
+/// Lets code observe the termination of a scope, e.g. to shut down
+/// gracefully. Created with
+/// [`Scope::cancellation_token`][crate::Scope::cancellation_token], and handed
+/// to jobs spawned with [`Scope::spawn_graceful`][crate::Scope::spawn_graceful].
+///
+/// Terminating a scope stops its jobs at their next await point, and
+/// [`cancelled`][Self::cancelled] resolves. Only jobs spawned with
+/// `spawn_graceful` keep running after that, for a bounded grace period, so
+/// that they can e.g. flush buffers before they finish. Holding a token never
+/// delays the scope.
+#[derive(Clone)]
+pub struct CancellationToken {
+    state: Arc<State>,
+}
+
+struct State {
+    cancelled: AtomicBool,
+    waiters: Mutex<Vec<Waker>>,
+}
+
+/// The cancellation state of a scope, shared with its tokens.
+pub(crate) struct Cancellation {
+    state: Arc<State>,
+}
+
+impl Cancellation {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::new(State {
+                cancelled: AtomicBool::new(false),
+                waiters: Mutex::new(vec![]),
+            }),
+        }
+    }
+
+    pub(crate) fn token(&self) -> CancellationToken {
+        CancellationToken {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Resolves every token's [`CancellationToken::cancelled`].
+    pub(crate) fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+        for waker in std::mem::take(&mut *self.state.waiters.lock().unwrap()) {
+            waker.wake();
+        }
+    }
+}
+
+impl CancellationToken {
+    /// Returns true once the scope was terminated.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Resolves once the scope is terminated.
+    pub async fn cancelled(&self) {
+        std::future::poll_fn(|cx| {
+            if self.is_cancelled() {
+                return Poll::Ready(());
+            }
+            let mut waiters = self.state.waiters.lock().unwrap();
+            if !waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                waiters.push(cx.waker().clone());
+            }
+            drop(waiters);
+            // Cancellation may have happened before we registered.
+            if self.is_cancelled() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
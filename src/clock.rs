@@ -0,0 +1,206 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, OnceLock,
+    },
+    task::{Context, Poll, Waker},
+    time::Instant,
+};
+
+use futures::future::BoxFuture;
+
+/// Source of the current time for the time-aware parts of a scope, such as
+/// [`Scope::spawn_timed`][crate::Scope::spawn_timed].
+///
+/// Scopes use [`SystemClock`] unless another clock is installed with
+/// [`ScopeBody::with_clock`][crate::ScopeBody::with_clock]. Installing a
+/// manually advanced clock makes timing-dependent code deterministic in tests.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{sync::{Arc, Mutex}, time::{Duration, Instant}};
+/// struct ManualClock(Mutex<Instant>);
+///
+/// impl moro::Clock for ManualClock {
+///     fn now(&self) -> Instant {
+///         *self.0.lock().unwrap()
+///     }
+/// }
+///
+/// # futures::executor::block_on(async {
+/// let clock = Arc::new(ManualClock(Mutex::new(Instant::now())));
+/// let timed = moro::async_scope!(|scope| {
+///     scope
+///         .spawn_timed(async { *clock.0.lock().unwrap() += Duration::from_secs(5) })
+///         .await
+/// })
+/// .with_clock(clock.clone())
+/// .await;
+/// assert_eq!(timed.elapsed, Duration::from_secs(5));
+/// # });
+/// ```
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Returns a future that completes once [`now`][Self::now] has reached
+    /// `deadline`, used by e.g.
+    /// [`Scope::spawn_rate_limited`][crate::Scope::spawn_rate_limited] to wait.
+    ///
+    /// The default implementation waits in real time, so that it works with
+    /// any executor: a single timer thread, started on first use, wakes all
+    /// sleeping tasks. Clocks whose time does not follow [`Instant::now`]
+    /// should override it; a simulated clock can simply advance its time to
+    /// `deadline`.
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        Box::pin(Sleep { deadline, id: None })
+    }
+}
+
+/// The default [`Clock`], backed by [`Instant::now`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::time::{Duration, Instant};
+/// use moro::{Clock, SystemClock};
+///
+/// # futures::executor::block_on(async {
+/// let start = Instant::now();
+/// // All sleeps share one timer thread.
+/// futures::future::join_all(
+///     (0..100).map(|i| SystemClock.sleep_until(start + Duration::from_millis(i % 10))),
+/// )
+/// .await;
+/// assert!(start.elapsed() >= Duration::from_millis(9));
+/// # });
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        C::now(self)
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        C::sleep_until(self, deadline)
+    }
+}
+
+/// Future returned by the default [`Clock::sleep_until`].
+struct Sleep {
+    deadline: Instant,
+
+    /// Key of our entry in the timer, once registered.
+    id: Option<u64>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            if let Some(id) = self.id.take() {
+                Timer::get().remove(self.deadline, id);
+            }
+            return Poll::Ready(());
+        }
+        let id = *self.id.get_or_insert_with(|| Timer::get().next_id());
+        Timer::get().register(self.deadline, id, cx.waker());
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            Timer::get().remove(self.deadline, id);
+        }
+    }
+}
+
+/// Wakes sleeping tasks from a single background thread.
+struct Timer {
+    /// One waker per pending [`Sleep`], by deadline; the id keeps keys unique.
+    wakers: Mutex<BTreeMap<(Instant, u64), Waker>>,
+    changed: Condvar,
+    next_id: AtomicU64,
+}
+
+impl Timer {
+    fn get() -> &'static Timer {
+        static TIMER: OnceLock<&'static Timer> = OnceLock::new();
+        TIMER.get_or_init(|| {
+            let timer: &'static Timer = Box::leak(Box::new(Timer {
+                wakers: Mutex::new(BTreeMap::new()),
+                changed: Condvar::new(),
+                next_id: AtomicU64::new(0),
+            }));
+            std::thread::Builder::new()
+                .name("moro-timer".into())
+                .spawn(move || timer.run())
+                .expect("failed to spawn the timer thread");
+            timer
+        })
+    }
+
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn register(&self, deadline: Instant, id: u64, waker: &Waker) {
+        let mut wakers = self.wakers.lock().unwrap();
+        match wakers.get_mut(&(deadline, id)) {
+            Some(w) => w.clone_from(waker),
+            None => {
+                let earliest = wakers.keys().next().is_none_or(|&(d, _)| deadline < d);
+                wakers.insert((deadline, id), waker.clone());
+                if earliest {
+                    self.changed.notify_one();
+                }
+            }
+        }
+    }
+
+    fn remove(&self, deadline: Instant, id: u64) {
+        self.wakers.lock().unwrap().remove(&(deadline, id));
+    }
+
+    fn run(&self) {
+        let mut wakers = self.wakers.lock().unwrap();
+        let mut due = vec![];
+        loop {
+            let now = Instant::now();
+            while let Some(entry) = wakers.first_entry() {
+                if entry.key().0 > now {
+                    break;
+                }
+                due.push(entry.remove());
+            }
+            if !due.is_empty() {
+                // Wake outside the lock, wakers may run arbitrary code.
+                drop(wakers);
+                due.drain(..).for_each(Waker::wake);
+                wakers = self.wakers.lock().unwrap();
+                continue;
+            }
+            wakers = match wakers.keys().next() {
+                Some(&(deadline, _)) => {
+                    self.changed.wait_timeout(wakers, deadline - now).unwrap().0
+                }
+                None => self.changed.wait(wakers).unwrap(),
+            };
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Values of a scope's context, keyed by type; see
+/// [`ScopeBody::with_context`][crate::ScopeBody::with_context].
+#[derive(Default)]
+pub(crate) struct Contexts {
+    values: Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl Contexts {
+    pub(crate) fn insert<C: Send + Sync + 'static>(&self, value: C) {
+        self.values
+            .lock()
+            .unwrap()
+            .insert(TypeId::of::<C>(), Arc::new(value));
+    }
+
+    pub(crate) fn get<C: Clone + 'static>(&self) -> Option<C> {
+        let values = self.values.lock().unwrap();
+        values.get(&TypeId::of::<C>())?.downcast_ref::<C>().cloned()
+    }
+
+    /// Copies every value of `other` that is not set in `self`.
+    pub(crate) fn inherit(&self, other: &Contexts) {
+        let other = other.values.lock().unwrap().clone();
+        let mut values = self.values.lock().unwrap();
+        for (id, value) in other {
+            values.entry(id).or_insert(value);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(feature = "events")]
+use std::sync::Mutex;
+
+/// A lifecycle event of a scope, see [`Scope::events`][crate::Scope::events].
+///
+/// With the `tracing` feature, every event is also recorded as a
+/// `trace`-level [`tracing`](https://docs.rs/tracing) event. Jobs are then
+/// polled within a `moro::job` span, carrying the job's `id`, the `label`
+/// given to [`Scope::spawn_named`][crate::Scope::spawn_named] and the
+/// `location` the job was spawned at, inside a `moro::poll_jobs` span for
+/// each time the scope polls its jobs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(any(feature = "events", feature = "tracing")), allow(dead_code))]
+pub enum ScopeEvent {
+    /// A job was spawned; `id` is the job's [`JobInfo::id`][crate::JobInfo::id].
+    Spawned { id: u64 },
+
+    /// A job ran to completion.
+    Completed { id: u64 },
+
+    /// The scope was terminated, e.g. with [`Scope::terminate`][crate::Scope::terminate].
+    Cancelled,
+
+    /// A job was dropped before completing, because the scope was terminated
+    /// or the job itself was cancelled.
+    Terminated { id: u64 },
+}
+
+/// The subscribers to a scope's events. Without the `events` feature, this is
+/// empty and emitting an event does nothing.
+#[derive(Default)]
+pub(crate) struct Events {
+    #[cfg(feature = "events")]
+    subscribers: Mutex<Vec<futures::channel::mpsc::UnboundedSender<ScopeEvent>>>,
+}
+
+impl Events {
+    /// Sends the event created by `event` to every subscriber.
+    #[cfg_attr(not(feature = "events"), allow(unused_variables))]
+    pub(crate) fn emit(&self, event: impl FnOnce() -> ScopeEvent) {
+        #[cfg(feature = "tracing")]
+        let event = {
+            let event = event();
+            tracing::trace!(?event, "moro::event");
+            move || event
+        };
+
+        #[cfg(feature = "events")]
+        {
+            let mut subscribers = self.subscribers.lock().unwrap();
+            if subscribers.is_empty() {
+                return;
+            }
+            let event = event();
+            subscribers.retain(|tx| tx.unbounded_send(event.clone()).is_ok());
+        }
+    }
+
+    #[cfg(feature = "events")]
+    pub(crate) fn subscribe(&self) -> ScopeEvents {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        self.subscribers.lock().unwrap().push(tx);
+        ScopeEvents { rx }
+    }
+}
+
+/// Stream of a scope's lifecycle events, see [`Scope::events`][crate::Scope::events].
+#[cfg(feature = "events")]
+pub struct ScopeEvents {
+    rx: futures::channel::mpsc::UnboundedReceiver<ScopeEvent>,
+}
+
+#[cfg(feature = "events")]
+impl futures::Stream for ScopeEvents {
+    type Item = ScopeEvent;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<ScopeEvent>> {
+        std::pin::Pin::new(&mut self.rx).poll_next(cx)
+    }
+}
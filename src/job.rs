@@ -0,0 +1,188 @@
+use std::{
+    borrow::Cow,
+    panic::Location,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use futures::{channel::oneshot, future::AbortHandle};
+
+use crate::events::{Events, ScopeEvent};
+
+/// Bookkeeping for a job that is alive in a scope.
+pub(crate) struct JobEntry {
+    /// Priority given to [`Scope::spawn_prioritized`][crate::Scope::spawn_prioritized],
+    /// or `None` for jobs that cannot be preempted.
+    pub(crate) priority: Option<u32>,
+
+    /// Aborts the job: it completes at its next poll, dropping its future.
+    pub(crate) abort: AbortHandle,
+
+    /// When the job was spawned, according to the scope's clock.
+    pub(crate) spawned_at: Instant,
+
+    /// Where in the user's code the job was spawned.
+    pub(crate) location: &'static Location<'static>,
+
+    /// Label given to [`Scope::spawn_named`][crate::Scope::spawn_named].
+    pub(crate) label: Option<Cow<'static, str>>,
+
+    /// Set for jobs spawned with [`Scope::spawn_graceful`][crate::Scope::spawn_graceful],
+    /// which keep running for a while once the scope is terminated.
+    pub(crate) graceful: bool,
+}
+
+impl JobEntry {
+    pub(crate) fn info(&self, id: u64, now: Instant) -> JobInfo {
+        JobInfo {
+            id,
+            priority: self.priority,
+            age: now.saturating_duration_since(self.spawned_at),
+            location: self.location,
+            label: self.label.clone(),
+        }
+    }
+}
+
+/// A snapshot of a job that is in flight in a scope, see
+/// [`Scope::pending_jobs`][crate::Scope::pending_jobs] and
+/// [`Scope::cancel_if`][crate::Scope::cancel_if].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct JobInfo {
+    /// Identifies the job within its scope; ids are assigned in spawn order.
+    pub id: u64,
+
+    /// The job's priority, if it was spawned with
+    /// [`Scope::spawn_prioritized`][crate::Scope::spawn_prioritized].
+    pub priority: Option<u32>,
+
+    /// Time since the job was spawned, according to the scope's
+    /// [`Clock`][crate::Clock].
+    pub age: Duration,
+
+    /// The call site that spawned the job, e.g. the call to
+    /// [`Scope::spawn`][crate::Scope::spawn]. Useful to find out which jobs
+    /// a stalled scope is waiting for.
+    pub location: &'static Location<'static>,
+
+    /// The label the job was spawned with, if it was spawned with
+    /// [`Scope::spawn_named`][crate::Scope::spawn_named].
+    pub label: Option<Cow<'static, str>>,
+}
+
+/// Returned by `Scope::spawn_job` for a newly registered job.
+pub(crate) struct JobHandle<T> {
+    /// Receives the job's result; canceled if the job is aborted.
+    pub(crate) rx: oneshot::Receiver<T>,
+    pub(crate) abort: AbortHandle,
+
+    /// Set once the job's result has been sent to `rx`.
+    pub(crate) finished: Arc<AtomicBool>,
+}
+
+pub(crate) type JobTable = Mutex<Jobs>;
+
+/// The jobs alive in a scope. Jobs are stored in slots that are reused once
+/// they complete, so that registering a job on spawn neither hashes nor,
+/// once the scope has warmed up, allocates.
+#[derive(Default)]
+pub(crate) struct Jobs {
+    /// Each job's id and entry, by slot.
+    slots: Vec<Option<(u64, JobEntry)>>,
+    /// Empty slots, to be reused first.
+    vacant: Vec<usize>,
+    len: usize,
+}
+
+impl Jobs {
+    /// Stores `entry` for the job `id`, returning its slot.
+    pub(crate) fn insert(&mut self, id: u64, entry: JobEntry) -> usize {
+        self.len += 1;
+        match self.vacant.pop() {
+            Some(slot) => {
+                self.slots[slot] = Some((id, entry));
+                slot
+            }
+            None => {
+                self.slots.push(Some((id, entry)));
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// Removes the entry of job `id` from `slot`, unless it was removed
+    /// already (and the slot possibly reused).
+    pub(crate) fn remove(&mut self, slot: usize, id: u64) -> Option<JobEntry> {
+        match &self.slots[slot] {
+            Some((occupant, _)) if *occupant == id => {
+                self.len -= 1;
+                self.vacant.push(slot);
+                self.slots[slot].take().map(|(_, entry)| entry)
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Each job's slot, id and entry, in no particular order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, u64, &JobEntry)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, job)| job.as_ref().map(|(id, entry)| (slot, *id, entry)))
+    }
+
+    /// Releases the memory of empty slots past the last job.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        while let Some(None) = self.slots.last() {
+            self.slots.pop();
+        }
+        let len = self.slots.len();
+        self.vacant.retain(|&slot| slot < len);
+        self.slots.shrink_to_fit();
+        self.vacant.shrink_to_fit();
+    }
+
+    /// Number of jobs that fit without allocating.
+    #[cfg(feature = "test-util")]
+    pub(crate) fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+}
+
+/// Held by a running job; removes the job's entry from the table when the job
+/// completes or is dropped.
+pub(crate) struct JobGuard<'scope> {
+    pub(crate) jobs: &'scope JobTable,
+    pub(crate) events: &'scope Events,
+    pub(crate) id: u64,
+    pub(crate) slot: usize,
+
+    /// The scope's count of graceful jobs, if this is one.
+    pub(crate) graceful: Option<&'scope AtomicUsize>,
+
+    /// Set once the job has run to completion.
+    pub(crate) completed: bool,
+}
+
+impl Drop for JobGuard<'_> {
+    fn drop(&mut self) {
+        self.jobs.lock().unwrap().remove(self.slot, self.id);
+        if let Some(graceful) = self.graceful {
+            graceful.fetch_sub(1, Ordering::Release);
+        }
+        let id = self.id;
+        if self.completed {
+            self.events.emit(|| ScopeEvent::Completed { id });
+        } else {
+            self.events.emit(|| ScopeEvent::Terminated { id });
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::{cmp::Reverse, collections::BinaryHeap, panic::Location};
+
+use futures::{channel::mpsc, Future, StreamExt};
+
+use crate::{AsyncIterator, Scope};
+
+/// A job of a [`JobSet`] that finished; `value` is `None` if the job was
+/// rejected or preempted by the scope's budget.
+struct Completion<T> {
+    /// Position of the job among those spawned into the set.
+    index: usize,
+    priority: u32,
+    value: Option<T>,
+}
+
+/// A set of jobs in a scope whose results are yielded in the order the jobs
+/// complete. Created with [`Scope::job_set`] or [`Scope::results`].
+///
+/// `JobSet` is an [`AsyncIterator`], so the results can be processed with the
+/// iterator combinators. The iterator ends once every job spawned into the
+/// set has completed and its result has been yielded.
+pub struct JobSet<'scope, 'env, R, T>
+where
+    R: Send + 'env,
+{
+    scope: &'scope Scope<'scope, 'env, R>,
+    tx: mpsc::UnboundedSender<Completion<T>>,
+    rx: mpsc::UnboundedReceiver<Completion<T>>,
+
+    /// Number of jobs whose result has not yet been yielded.
+    pending: usize,
+
+    /// Number of jobs spawned into the set so far.
+    spawned: usize,
+}
+
+impl<'scope, 'env, R, T> JobSet<'scope, 'env, R, T>
+where
+    R: Send + 'env,
+    T: Send + 'scope,
+{
+    pub(crate) fn new(scope: &'scope Scope<'scope, 'env, R>) -> Self {
+        let (tx, rx) = mpsc::unbounded();
+        Self {
+            scope,
+            tx,
+            rx,
+            pending: 0,
+            spawned: 0,
+        }
+    }
+
+    /// Spawns a job into the scope whose result will be yielded by this set.
+    ///
+    /// For the purposes of [`by_priority`][Self::by_priority], the job has
+    /// priority 0.
+    #[track_caller]
+    pub fn spawn(&mut self, future: impl Future<Output = T> + Send + 'scope) {
+        self.spawn_at(Location::caller(), future);
+    }
+
+    /// Like [`spawn`][Self::spawn], recording `location` as the place the job
+    /// was spawned, see `Scope::spawn_at`.
+    pub(crate) fn spawn_at(
+        &mut self,
+        location: &'static Location<'static>,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) {
+        self.push(location, 0, async { Some(future.await) });
+    }
+
+    /// Spawns a job into the scope with
+    /// [`Scope::spawn_prioritized`], so that it is subject to the scope's
+    /// budget. Jobs that are rejected or preempted yield no result.
+    #[track_caller]
+    pub fn spawn_prioritized(
+        &mut self,
+        priority: u32,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) {
+        let job = self.scope.spawn_prioritized(priority, future);
+        self.push(Location::caller(), priority, job);
+    }
+
+    fn push(
+        &mut self,
+        location: &'static Location<'static>,
+        priority: u32,
+        job: impl Future<Output = Option<T>> + Send + 'scope,
+    ) {
+        let index = self.spawned;
+        self.spawned += 1;
+        self.pending += 1;
+        let tx = self.tx.clone();
+        drop(self.scope.spawn_at(location, async move {
+            let value = job.await;
+            // Fails only if the set was dropped, in which case nobody
+            // is interested in the result.
+            let _ = tx.unbounded_send(Completion {
+                index,
+                priority,
+                value,
+            });
+        }));
+    }
+
+    /// Yields the results in priority order among the jobs that have
+    /// completed, instead of pure completion order.
+    ///
+    /// Whenever a result is requested, every job that has completed so far
+    /// is collected, and the result with the highest priority is yielded
+    /// first; so high-priority results surface first when several jobs finish
+    /// together. Results of equal priority are yielded in completion order.
+    /// If no job has completed yet, the first one to complete is yielded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let (ready_tx, ready_rx) = async_channel::bounded::<()>(1);
+    /// let order = moro::scope(async |scope| {
+    ///     let mut set = scope.job_set();
+    ///     for (name, priority) in [("a", 1), ("b", 3), ("c", 2), ("d", 3)] {
+    ///         let ready = ready_rx.clone();
+    ///         set.spawn_prioritized(priority, async move {
+    ///             // All jobs finish in the same batch.
+    ///             let _ = ready.recv().await;
+    ///             name
+    ///         });
+    ///     }
+    ///     drop(ready_tx);
+    ///     let mut results = set.by_priority();
+    ///     let mut order = vec![];
+    ///     while let Some(name) = results.next().await {
+    ///         order.push(name);
+    ///     }
+    ///     order
+    /// })
+    /// .await;
+    /// assert_eq!(order, ["b", "d", "c", "a"]);
+    /// # });
+    /// ```
+    pub fn by_priority(self) -> ByPriority<'scope, 'env, R, T> {
+        ByPriority {
+            set: self,
+            ready: BinaryHeap::new(),
+            received: 0,
+        }
+    }
+
+    /// Waits for every job in the set and returns their results in the order
+    /// the jobs were spawned, whatever order they complete in.
+    ///
+    /// Results that were already yielded by [`next`][AsyncIterator::next]
+    /// are not included, nor are jobs rejected or preempted by the scope's
+    /// budget. The jobs keep running in the scope while this waits, so they
+    /// do not depend on the caller to make progress.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::test_util::yield_now;
+    /// # futures::executor::block_on(async {
+    /// let result = moro::scope(async |scope| {
+    ///     let mut set = scope.job_set();
+    ///     for i in 0..3 {
+    ///         set.spawn(async move {
+    ///             // Earlier jobs finish later.
+    ///             for _ in i..3 {
+    ///                 yield_now().await;
+    ///             }
+    ///             i * 10
+    ///         });
+    ///     }
+    ///     set.join_ordered().await
+    /// })
+    /// .await;
+    /// assert_eq!(result, [0, 10, 20]);
+    /// # });
+    /// ```
+    pub async fn join_ordered(mut self) -> Vec<T> {
+        let mut results = Vec::with_capacity(self.pending);
+        while self.pending > 0 {
+            // The set holds a sender, so the channel never closes.
+            let Some(completion) = self.rx.next().await else {
+                break;
+            };
+            self.pending -= 1;
+            if let Some(value) = completion.value {
+                results.push((completion.index, value));
+            }
+        }
+        results.sort_by_key(|&(index, _)| index);
+        results.into_iter().map(|(_, value)| value).collect()
+    }
+
+    /// Number of jobs whose result has not yet been yielded.
+    pub fn len(&self) -> usize {
+        self.pending
+    }
+
+    /// True if every result has been yielded.
+    pub fn is_empty(&self) -> bool {
+        self.pending == 0
+    }
+}
+
+impl<R, T> AsyncIterator for JobSet<'_, '_, R, T>
+where
+    R: Send,
+{
+    type Item = T;
+
+    async fn next(&mut self) -> Option<T> {
+        while self.pending > 0 {
+            // The set holds a sender, so the channel never closes.
+            let completion = self.rx.next().await?;
+            self.pending -= 1;
+            if completion.value.is_some() {
+                return completion.value;
+            }
+        }
+        None
+    }
+}
+
+/// The results of a [`JobSet`] in priority order among completed jobs.
+/// Created with [`JobSet::by_priority`].
+pub struct ByPriority<'scope, 'env, R, T>
+where
+    R: Send + 'env,
+{
+    set: JobSet<'scope, 'env, R, T>,
+
+    /// Completed results, keyed by priority and then completion order.
+    ready: BinaryHeap<Ready<T>>,
+    received: u64,
+}
+
+struct Ready<T> {
+    key: (u32, Reverse<u64>),
+    value: T,
+}
+
+impl<T> PartialEq for Ready<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T> Eq for Ready<T> {}
+
+impl<T> PartialOrd for Ready<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Ready<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<R, T> ByPriority<'_, '_, R, T>
+where
+    R: Send,
+{
+    fn receive(&mut self, completion: Completion<T>) {
+        self.set.pending -= 1;
+        if let Some(value) = completion.value {
+            self.received += 1;
+            self.ready.push(Ready {
+                key: (completion.priority, Reverse(self.received)),
+                value,
+            });
+        }
+    }
+}
+
+impl<R, T> AsyncIterator for ByPriority<'_, '_, R, T>
+where
+    R: Send,
+{
+    type Item = T;
+
+    async fn next(&mut self) -> Option<T> {
+        while self.ready.is_empty() && self.set.pending > 0 {
+            let completion = self.set.rx.next().await?;
+            self.receive(completion);
+        }
+        while let Ok(completion) = self.set.rx.try_recv() {
+            self.receive(completion);
+        }
+        self.ready.pop().map(|ready| ready.value)
+    }
+}
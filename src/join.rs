@@ -0,0 +1,129 @@
+use std::panic::Location;
+
+use futures::Future;
+
+use crate::Scope;
+
+/// Awaits all of `handles`, typically the handles of fallible jobs, and
+/// returns every outcome, in the order of `handles`.
+///
+/// Failures neither short-circuit nor cancel anything: each job runs to
+/// completion, and its `Ok` or `Err` ends up at its position in the result.
+/// This suits e.g. a dashboard that reports the status of every job.
+///
+/// # Examples
+///
+/// ```rust
+/// # futures::executor::block_on(async {
+/// let statuses = moro::async_scope!(|scope| {
+///     let checks = (0..4).map(|i| {
+///         scope.spawn(async move {
+///             if i % 2 == 0 {
+///                 Ok(i)
+///             } else {
+///                 Err(format!("check {i} failed"))
+///             }
+///         })
+///     });
+///     moro::join_all_results(checks).await
+/// })
+/// .await;
+/// assert_eq!(
+///     statuses,
+///     [
+///         Ok(0),
+///         Err("check 1 failed".to_string()),
+///         Ok(2),
+///         Err("check 3 failed".to_string()),
+///     ]
+/// );
+/// # });
+/// ```
+pub async fn join_all_results<O, E, F>(handles: impl IntoIterator<Item = F>) -> Vec<Result<O, E>>
+where
+    F: Future<Output = Result<O, E>>,
+{
+    futures::future::join_all(handles).await
+}
+
+/// Spawns each of `jobs` into `scope` and returns their results, in the order
+/// of `jobs` (not the order they complete in).
+///
+/// This is shorthand for spawning every job, collecting the handles in a
+/// `Vec`, and awaiting each one. If a job terminates the scope, the scope's
+/// result is its termination value as usual.
+///
+/// # Examples
+///
+/// ```rust
+/// # futures::executor::block_on(async {
+/// let lengths = moro::async_scope!(|scope| {
+///     let jobs = ["hello", "moro", "!"].map(|word| async move { word.len() });
+///     moro::join_all(scope, jobs).await
+/// })
+/// .await;
+/// assert_eq!(lengths, [5, 4, 1]);
+/// # });
+/// ```
+#[track_caller]
+pub fn join_all<'scope, 'env, R, T, J>(
+    scope: &'scope Scope<'scope, 'env, R>,
+    jobs: impl IntoIterator<Item = J>,
+) -> impl Future<Output = Vec<T>> + Send + 'scope
+where
+    R: Send + 'env,
+    T: Send + 'scope,
+    J: Future<Output = T> + Send + 'scope,
+{
+    let location = Location::caller();
+    let handles: Vec<_> = jobs
+        .into_iter()
+        .map(|job| scope.spawn_at(location, job))
+        .collect();
+    futures::future::join_all(handles)
+}
+
+/// Like [`join_all`], but for fallible jobs: the first job to fail
+/// terminates the scope with its error, canceling the others.
+///
+/// The returned future thus only completes if every job succeeds. Unlike
+/// [`Spawned::or_cancel`][crate::Spawned::or_cancel], a failure terminates
+/// the scope as soon as it happens, even if the results of the jobs before it
+/// are not yet awaited.
+///
+/// # Examples
+///
+/// ```rust
+/// # futures::executor::block_on(async {
+/// let parse = |inputs: &'static [&'static str]| {
+///     moro::async_scope!(|scope| {
+///         let jobs = inputs.iter().map(|input| async move { input.parse::<u32>() });
+///         let numbers = moro::try_join_all(scope, jobs).await;
+///         Ok(numbers.iter().sum::<u32>())
+///     })
+/// };
+/// assert_eq!(parse(&["1", "2", "3"]).await, Ok(6));
+/// assert!(parse(&["1", "two", "3"]).await.is_err());
+/// # });
+/// ```
+#[track_caller]
+pub fn try_join_all<'scope, 'env, V, T, E, J>(
+    scope: &'scope Scope<'scope, 'env, Result<V, E>>,
+    jobs: impl IntoIterator<Item = J>,
+) -> impl Future<Output = Vec<T>> + Send + 'scope
+where
+    V: Send + 'env,
+    E: Send + 'env,
+    T: Send + 'scope,
+    J: Future<Output = Result<T, E>> + Send + 'scope,
+{
+    join_all(
+        scope,
+        jobs.into_iter().map(move |job| async move {
+            match job.await {
+                Ok(value) => value,
+                Err(error) => scope.terminate(Err(error)).await,
+            }
+        }),
+    )
+}
@@ -1,24 +1,70 @@
-#![feature(async_closure)]
 #![feature(async_fn_traits)]
 #![feature(unboxed_closures)]
+#![feature(async_trait_bounds)]
 #![allow(async_fn_in_trait)]
 
-use std::ops::AsyncFnOnce;
+use std::{convert::Infallible, ops::AsyncFnOnce};
 
 #[macro_use]
 mod macros;
 
+mod adaptive;
 mod async_iter;
 mod body;
+mod borrows;
+mod broadcast;
+mod cancel_on_drop;
+mod cancellation;
+mod clock;
+mod context;
+mod events;
+mod job;
+mod job_set;
+mod join;
+mod limited;
+mod local_scope;
+mod outputs;
+mod pipeline;
+mod poll_jobs;
 pub mod prelude;
+mod rate_limit;
+mod restart;
 mod result_ext;
+mod retry;
 mod scope;
 mod scope_body;
+mod self_limit;
+mod shared;
 mod spawned;
+mod steal;
 mod stream;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod timed;
+mod waker;
 
-pub use async_iter::{AsyncIterator, IntoAsyncIter};
+pub use adaptive::AdaptiveConfig;
+pub use async_iter::{from_iter, AsyncIterator, IntoAsyncIter};
+pub use async_iter::{from_stream, into_stream};
+pub use borrows::Borrows;
+pub use broadcast::{BroadcastReceiver, BroadcastSender};
+pub use cancel_on_drop::CancelOnDrop;
+pub use cancellation::CancellationToken;
+pub use clock::{Clock, SystemClock};
+#[cfg(feature = "events")]
+pub use events::{ScopeEvent, ScopeEvents};
+pub use job::JobInfo;
+pub use job_set::{ByPriority, JobSet};
+pub use join::{join_all, join_all_results, try_join_all};
+pub use limited::LimitedScope;
+pub use local_scope::{LocalResultScopeBody, LocalScope, LocalScopeFuture};
+pub use outputs::{Outputs, Yielder};
+pub use pipeline::{pipeline, Pipeline};
+pub use restart::PendingJob;
+pub use retry::{Jitter, RetryPolicy};
+pub use shared::SharedHandle;
 pub use stream::Stream;
+pub use timed::Timed;
 
 /// Creates an async scope within which you can spawn jobs.
 /// This works much like the stdlib's
@@ -30,20 +76,30 @@ pub use stream::Stream;
 /// can refer to stack variables that are defined outside
 /// the scope.
 ///
+/// The body is boxed (see [`scope_fn`]), so the resulting [`ScopeBody`] is
+/// `Unpin` and can be passed directly to combinators like
+/// [`select`](futures::future::select), but the body must be `Send`. To avoid
+/// the allocation, or for a body that is not `Send` (e.g. one driving an
+/// [`AsyncIterator`]), use [`scope`] with an async closure instead, which
+/// yields an unboxed, `!Unpin` scope.
+///
 /// # Cancellable vs infallible scopes
 ///
 /// By default, moro scopes support *cancellation*,
 /// which means that you can cancel the entire scope by invoking
-/// `scope.cancel(v)`. Cancellable scopes return a [`Result`] value
+/// `scope.cancel(v)`, an alias of [`scope.terminate(v)`][Scope::terminate].
+/// Cancellable scopes return a [`Result`] value
 /// whose error type is the type of `v`. If your scope does not use `cancel`,
 /// you will get compilation errors because the error type cannot be inferred!
 ///
-/// To avoid this, use the `infallible` method on the scope to convert it
-/// into a non-cancellable scope:
+/// To avoid this, use the [`infallible`][ScopeBody::infallible] method on
+/// the scope to convert it into a non-cancellable scope, or name the result
+/// type with the `->` syntax shown below:
 ///
 /// ```rust
 /// # futures::executor::block_on(async {
-/// let scope = moro::async_scope!(|scope| {/* ... */}).await;
+/// let value: u32 = moro::async_scope!(|scope| { Ok(22) }).infallible().await;
+/// # assert_eq!(value, 22);
 /// # });
 /// ```
 ///
@@ -87,8 +143,8 @@ pub use stream::Stream;
 /// ## Hello, world
 ///
 /// The following scope spawns one concurrent task which iterates over
-/// the vector `v` and sums its values; the [`infallible`][`Scope::infallible`]
-/// method is used to indicate that the scope is never cancelled:
+/// the vector `v` and sums its values. The scope is never cancelled, and its
+/// result is the value of the body:
 ///
 /// ```rust
 /// # futures::executor::block_on(async {
@@ -144,13 +200,250 @@ macro_rules! async_scope {
     }};
 }
 
-use futures::future::BoxFuture;
+/// Like [`async_scope!`], for scopes whose result is a `Result`: the first
+/// error cancels the scope, so the awaited result is ready for `?`.
+///
+/// The scope completes with `Err(e)` as soon as either the body fails, e.g.
+/// through `?`, or a job fails through [`or_cancel`][Spawned::or_cancel] or
+/// [`spawn_checked`][Scope::spawn_checked]; the jobs still running are
+/// cancelled rather than awaited. The error type is inferred from those
+/// calls, so it does not need to be spelled out with `-> Result<T, E>`.
+///
+/// # Examples
+///
+/// ```rust
+/// async fn total(prices: &[&str]) -> Result<u32, std::num::ParseIntError> {
+///     let total = moro::try_scope!(|scope| {
+///         let mut total = 0;
+///         for price in &prices[1..] {
+///             total += scope.spawn(async move { price.parse::<u32>() }).or_cancel(scope).await;
+///         }
+///         let first: u32 = prices[0].parse()?;
+///         Ok(first + total)
+///     })
+///     .await?;
+///     Ok(total * 2)
+/// }
+///
+/// # futures::executor::block_on(async {
+/// assert_eq!(total(&["1", "2", "3"]).await, Ok(12));
+/// assert!(total(&["1", "two", "3"]).await.is_err());
+/// assert!(total(&["one", "2", "3"]).await.is_err());
+/// # });
+/// ```
+///
+/// An error returned by the body does not wait for the jobs:
+///
+/// ```rust
+/// # futures::executor::block_on(async {
+/// let result: Result<(), std::num::ParseIntError> = moro::try_scope!(|scope| {
+///     scope.spawn(futures::future::pending::<()>());
+///     "two".parse::<u32>()?;
+///     Ok(())
+/// })
+/// .await;
+/// assert!(result.is_err());
+/// # });
+/// ```
+#[macro_export]
+macro_rules! try_scope {
+    (|$scope:ident| $body:expr) => {{
+        $crate::scope_fn(|$scope| {
+            let future = async {
+                match async { $body }.await {
+                    ::std::result::Result::Ok(value) => ::std::result::Result::Ok(value),
+                    ::std::result::Result::Err(error) => {
+                        $scope.terminate(::std::result::Result::Err(error)).await
+                    }
+                }
+            };
+            Box::pin(future)
+        })
+    }};
+}
+
+/// A scope future with a nameable type, returned by [`scope_boxed!`].
+///
+/// The type of a scope created with [`async_scope!`] mentions the type of its
+/// body, which cannot be named. This makes it impossible to return a scope
+/// from a trait method without an `async fn`, e.g. in a dyn-compatible trait.
+/// A `ScopeFuture` can be returned from anywhere.
+pub type ScopeFuture<'env, R> = BoxFuture<'env, R>;
+
+/// Like [`async_scope!`], but returns the scope as a [`ScopeFuture`], so that
+/// it can be returned from functions and trait methods whose return type
+/// must be nameable.
+///
+/// Since the scope outlives the function creating it, the body takes
+/// ownership of the variables it uses, like an `async move` block. To share
+/// data with the jobs, move references into the body.
+///
+/// # Examples
+///
+/// ```rust
+/// trait Job {
+///     fn run(&self) -> moro::ScopeFuture<'_, u32>;
+/// }
+///
+/// struct Sum(Vec<u32>);
+///
+/// impl Job for Sum {
+///     fn run(&self) -> moro::ScopeFuture<'_, u32> {
+///         moro::scope_boxed!(|scope| {
+///             let halves = self.0.split_at(self.0.len() / 2);
+///             let a = scope.spawn(async { halves.0.iter().sum::<u32>() });
+///             let b = scope.spawn(async { halves.1.iter().sum::<u32>() });
+///             a.await + b.await
+///         })
+///     }
+/// }
+///
+/// # futures::executor::block_on(async {
+/// let job: Box<dyn Job> = Box::new(Sum(vec![1, 2, 3, 4]));
+/// assert_eq!(job.run().await, 10);
+/// # });
+/// ```
+#[macro_export]
+macro_rules! scope_boxed {
+    (|$scope:ident| -> $result:ty { $($body:tt)* }) => {{
+        let scope: $crate::ScopeFuture<'_, $result> =
+            ::std::boxed::Box::pin($crate::scope_fn::<$result, _>(move |$scope| {
+                let future = async move { $($body)* };
+                ::std::boxed::Box::pin(future)
+            }));
+        scope
+    }};
+    (|$scope:ident| $body:expr) => {{
+        let scope: $crate::ScopeFuture<'_, _> =
+            ::std::boxed::Box::pin($crate::scope_fn(move |$scope| {
+                let future = async move { $body };
+                ::std::boxed::Box::pin(future)
+            }));
+        scope
+    }};
+}
+
+/// Creates a scope that collects the results of its jobs: the body spawns
+/// jobs into `jobs`, a [`JobSet`], and the scope evaluates to a `Vec` of
+/// their results, in the order the jobs complete.
+///
+/// The value of the body itself is discarded. Terminating the scope still
+/// works as usual, with a `Vec` of the caller's choosing. To collect results
+/// in spawn order instead, see [`join_all`].
+///
+/// # Examples
+///
+/// ```rust
+/// # futures::executor::block_on(async {
+/// let words = ["a", "bb", "ccc"];
+/// let mut lengths = moro::collect_scope!(|scope, jobs| {
+///     for word in words {
+///         jobs.spawn(async move { word.len() });
+///     }
+/// })
+/// .await;
+/// lengths.sort();
+/// assert_eq!(lengths, [1, 2, 3]);
+/// # });
+/// ```
+#[macro_export]
+macro_rules! collect_scope {
+    (|$scope:ident, $jobs:ident| $body:expr) => {{
+        $crate::scope(async |$scope| {
+            let mut $jobs = $scope.job_set();
+            $body;
+            let mut results = ::std::vec::Vec::with_capacity($jobs.len());
+            while let ::std::option::Option::Some(result) =
+                $crate::AsyncIterator::next(&mut $jobs).await
+            {
+                results.push(result);
+            }
+            results
+        })
+    }};
+}
+
+/// Like [`async_scope!`], but creates a [`LocalScope`], whose jobs and result
+/// need not be `Send`.
+///
+/// The scope runs on the current thread, so this suits single-threaded
+/// executors, such as GUI event loops or wasm, where futures commonly share
+/// `Rc<RefCell<..>>` state.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{cell::RefCell, rc::Rc};
+/// # futures::executor::block_on(async {
+/// let state = Rc::new(RefCell::new(vec![]));
+/// let result: Result<usize, String> = moro::local_scope!(|scope| {
+///     for name in ["button", "slider"] {
+///         let state = state.clone();
+///         scope.spawn(async move { state.borrow_mut().push(name) });
+///     }
+///     let len = scope.spawn(async { state.borrow().len() }).await;
+///     let checked = scope.spawn(async move {
+///         if len > 5 { Err(format!("too many widgets: {len}")) } else { Ok(len) }
+///     });
+///     Ok(checked.or_cancel_local(scope).await)
+/// })
+/// .await;
+/// assert_eq!(result, Ok(2));
+/// assert_eq!(*state.borrow(), ["button", "slider"]);
+/// # });
+/// ```
+///
+/// The jobs are polled by the scope itself, on the thread awaiting it, so on
+/// tokio's current-thread runtime neither `tokio::task::LocalSet` nor
+/// `spawn_local` is needed:
+///
+/// ```rust
+/// # use std::{cell::RefCell, collections::HashMap, rc::Rc};
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// let cache: Rc<RefCell<HashMap<u32, u32>>> = Rc::default();
+/// let total = runtime.block_on(moro::local_scope!(|scope| -> u32 {
+///     let jobs: Vec<_> = (1..=3)
+///         .map(|n| {
+///             let cache = cache.clone();
+///             scope.spawn(async move { *cache.borrow_mut().entry(n).or_insert(n * n) })
+///         })
+///         .collect();
+///     futures::future::join_all(jobs).await.into_iter().sum()
+/// }));
+/// assert_eq!(total, 14);
+/// assert_eq!(cache.borrow().len(), 3);
+/// ```
+#[macro_export]
+macro_rules! local_scope {
+    (|$scope:ident| -> $result:ty { $($body:tt)* }) => {{
+        $crate::local_scope_fn::<$result, _>(|$scope| {
+            let future = async { $($body)* };
+            ::std::boxed::Box::pin(future)
+        })
+    }};
+    (|$scope:ident| $body:expr) => {{
+        $crate::local_scope_fn(|$scope| {
+            let future = async { $body };
+            ::std::boxed::Box::pin(future)
+        })
+    }};
+}
+
+use futures::{
+    future::{BoxFuture, LocalBoxFuture},
+    Future,
+};
 
 pub use self::scope::Scope;
-pub use self::scope_body::ScopeBody;
+pub use self::scope_body::{ScopeBody, ScopeResults};
 pub use self::spawned::Spawned;
+pub use self::steal::StealableScopeGroup;
 
-/// Creates a new moro scope. Normally, you invoke this through `moro::async_scope!`.
+/// Creates a new moro scope whose body returns a boxed future. Normally, you
+/// invoke this through `moro::async_scope!`.
+///
+/// Since the body's future is boxed, the returned scope is `Unpin` and has a
+/// nameable type; [`scope`] avoids the allocation.
 pub fn scope_fn<'env, R, B>(body: B) -> ScopeBody<'env, R, BoxFuture<'env, R>>
 where
     R: Send + 'env,
@@ -168,7 +461,178 @@ where
     ScopeBody::new(body::Body::new(body_future, scope))
 }
 
-/// Creates a new moro scope.
+/// Like [`scope_fn`], but the scope polls its jobs in a fixed order: whenever
+/// the scope is polled, every pending job is polled, earliest spawned first.
+///
+/// By default, jobs are only polled once they have been woken, in an order
+/// that depends on the order of the wakeups. Biased polling is less efficient
+/// but deterministic, which makes it useful for reproducible tests and
+/// simulations.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::sync::Mutex;
+/// # use moro::test_util::yield_now;
+/// # futures::executor::block_on(async {
+/// let log = Mutex::new(vec![]);
+/// moro::scope_fn_biased(|scope| {
+///     Box::pin(async {
+///         for name in ["a", "b"] {
+///             let log = &log;
+///             scope.spawn(async move {
+///                 for i in 0..3 {
+///                     log.lock().unwrap().push(format!("{name}{i}"));
+///                     yield_now().await;
+///                 }
+///             });
+///         }
+///     })
+/// })
+/// .await;
+/// assert_eq!(log.into_inner().unwrap(), ["a0", "b0", "a1", "b1", "a2", "b2"]);
+/// # });
+/// ```
+pub fn scope_fn_biased<'env, R, B>(body: B) -> ScopeBody<'env, R, BoxFuture<'env, R>>
+where
+    R: Send + 'env,
+    for<'scope> B: FnOnce(&'scope Scope<'scope, 'env, R>) -> BoxFuture<'scope, R>,
+{
+    let scope = Scope::new();
+    scope.set_biased();
+
+    // Unsafe: see `scope_fn`.
+    let scope_ref: *const Scope<'_, '_, R> = &*scope;
+    let body_future = body(unsafe { &*scope_ref });
+
+    ScopeBody::new(body::Body::new(body_future, scope))
+}
+
+/// Like [`scope_fn_biased`], but every time the scope is polled, its pending
+/// jobs are polled in a pseudo-random order determined by `seed`.
+///
+/// Running a test with many seeds explores many interleavings of its jobs,
+/// which helps to surface bugs that depend on the order jobs make progress in,
+/// such as deadlocks. Since the order only depends on the seed (given
+/// deterministic jobs), a failure can be reproduced by running the test with
+/// the seed that produced it. Requires the `fuzz-sched` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{collections::HashSet, sync::Mutex};
+/// # use moro::test_util::yield_now;
+/// fn run(seed: u64) -> Vec<String> {
+///     let log = Mutex::new(vec![]);
+///     futures::executor::block_on(moro::scope_fn_with_seed(seed, |scope| {
+///         Box::pin(async {
+///             for name in ["a", "b", "c"] {
+///                 let log = &log;
+///                 scope.spawn(async move {
+///                     for i in 0..2 {
+///                         log.lock().unwrap().push(format!("{name}{i}"));
+///                         yield_now().await;
+///                     }
+///                 });
+///             }
+///         })
+///     }));
+///     log.into_inner().unwrap()
+/// }
+///
+/// let mut interleavings = HashSet::new();
+/// for seed in 0..100 {
+///     let log = run(seed);
+///     // The scenario's invariant holds for every interleaving...
+///     for name in ["a", "b", "c"] {
+///         let first = log.iter().position(|e| *e == format!("{name}0"));
+///         let second = log.iter().position(|e| *e == format!("{name}1"));
+///         assert!(first < second, "seed {seed}: {log:?}");
+///     }
+///     // ...and every seed reproduces its interleaving.
+///     assert_eq!(log, run(seed));
+///     interleavings.insert(log);
+/// }
+/// assert!(interleavings.len() > 1);
+/// ```
+#[cfg(feature = "fuzz-sched")]
+pub fn scope_fn_with_seed<'env, R, B>(seed: u64, body: B) -> ScopeBody<'env, R, BoxFuture<'env, R>>
+where
+    R: Send + 'env,
+    for<'scope> B: FnOnce(&'scope Scope<'scope, 'env, R>) -> BoxFuture<'scope, R>,
+{
+    let scope = Scope::new();
+    scope.set_seed(seed);
+
+    // Unsafe: see `scope_fn`.
+    let scope_ref: *const Scope<'_, '_, R> = &*scope;
+    let body_future = body(unsafe { &*scope_ref });
+
+    ScopeBody::new(body::Body::new(body_future, scope))
+}
+
+/// Like [`scope_fn`], but the scope applies backpressure to its body when
+/// jobs are spawned faster than they complete.
+///
+/// While too many jobs are in flight, the scope stops polling the body, so a
+/// body that keeps spawning jobs (e.g. one per incoming item) pauses at its
+/// next `.await` until some complete, and the number of jobs in flight stays
+/// bounded instead of growing until memory or some other resource runs out.
+/// The limit is not fixed: it starts at 32 and is tuned by comparing the rate
+/// jobs are spawned at with the rate they complete at, shrinking while the
+/// body outpaces the jobs and growing while it does not.
+///
+/// Since the body is paused, jobs must not wait for the body (e.g. for
+/// messages it sends them) once they are numerous, or the scope deadlocks.
+/// Jobs spawned by other jobs are counted but not held back. For a fixed
+/// limit on running jobs, see [`Scope::with_concurrency_limit`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use moro::test_util::yield_now;
+/// /// Quickly spawns slow jobs, returning the most jobs ever in flight.
+/// async fn produce<'scope>(scope: &'scope moro::Scope<'scope, '_, usize>) -> usize {
+///     let mut max_in_flight = 0;
+///     for _ in 0..400 {
+///         scope.spawn(async {
+///             for _ in 0..200 {
+///                 yield_now().await;
+///             }
+///         });
+///         yield_now().await;
+///         max_in_flight = max_in_flight.max(scope.pending_jobs().len());
+///     }
+///     max_in_flight
+/// }
+///
+/// # futures::executor::block_on(async {
+/// let unlimited = moro::scope_fn(|scope| Box::pin(produce(scope))).await;
+/// let limited = moro::scope_fn_self_limiting(|scope| Box::pin(produce(scope))).await;
+/// assert!(unlimited > 150);
+/// assert!(limited <= 40);
+/// # });
+/// ```
+pub fn scope_fn_self_limiting<'env, R, B>(body: B) -> ScopeBody<'env, R, BoxFuture<'env, R>>
+where
+    R: Send + 'env,
+    for<'scope> B: FnOnce(&'scope Scope<'scope, 'env, R>) -> BoxFuture<'scope, R>,
+{
+    let scope = Scope::new();
+    scope.set_self_limiting();
+
+    // Unsafe: see `scope_fn`.
+    let scope_ref: *const Scope<'_, '_, R> = &*scope;
+    let body_future = body(unsafe { &*scope_ref });
+
+    ScopeBody::new(body::Body::new(body_future, scope))
+}
+
+/// Creates a new moro scope from an async closure.
+///
+/// Unlike [`async_scope!`], this does not box the body, so the returned scope
+/// is not `Unpin`; pin it with [`std::pin::pin!`] or
+/// [`ScopeBody::pinned`] where required.
 pub fn scope<'env, R, B>(
     body: B,
 ) -> ScopeBody<'env, R, <B as AsyncFnOnce<(&'env scope::Scope<'env, 'env, R>,)>>::CallOnceFuture>
@@ -187,3 +651,124 @@ where
 
     ScopeBody::new(body::Body::new(body_future, scope))
 }
+
+/// Creates a new moro scope whose result need not be `Send`, for use on
+/// single-threaded executors.
+///
+/// The body can return e.g. an `Rc`-based graph or GUI state. The jobs spawned
+/// into the scope must still be `Send`, since they share the scope with the
+/// body; only the body's own result is exempt. In exchange, the scope cannot
+/// be terminated early: its type is `Scope<'_, '_, Infallible>`, so there is
+/// no value to [`terminate`][Scope::terminate] it with. Have the body return a
+/// `Result` instead. For jobs that are not `Send` either, use
+/// [`local_scope!`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{cell::RefCell, rc::Rc};
+/// # futures::executor::block_on(async {
+/// let graph = moro::local_result_scope(async |scope| {
+///     let a = scope.spawn(async { 1 });
+///     let b = scope.spawn(async { 2 });
+///     Rc::new(RefCell::new(vec![a.await, b.await]))
+/// })
+/// .await;
+/// graph.borrow_mut().push(3);
+/// assert_eq!(*graph.borrow(), [1, 2, 3]);
+/// # });
+/// ```
+pub fn local_result_scope<'env, R, B>(
+    body: B,
+) -> LocalResultScopeBody<
+    'env,
+    R,
+    <B as AsyncFnOnce<(&'env scope::Scope<'env, 'env, Infallible>,)>>::CallOnceFuture,
+>
+where
+    R: 'env,
+    for<'scope> B: async FnOnce(&'scope Scope<'scope, 'env, Infallible>) -> R,
+{
+    let scope = Scope::new();
+
+    // Unsafe: see `scope`.
+    let scope_ref: *const Scope<'_, '_, Infallible> = &*scope;
+    let body_future = body(unsafe { &*scope_ref });
+
+    LocalResultScopeBody::new(body::Body::new(body_future, scope))
+}
+
+/// Creates a new [`LocalScope`]; usually invoked via [`local_scope!`].
+pub fn local_scope_fn<'env, R, B>(body: B) -> LocalScopeFuture<'env, R>
+where
+    R: 'env,
+    for<'scope> B: FnOnce(&'scope LocalScope<'scope, 'env, R>) -> LocalBoxFuture<'scope, R>,
+{
+    let scope = LocalScope::new();
+
+    // Unsafe: see `scope_fn`.
+    let scope_ref: *const LocalScope<'_, '_, R> = &*scope;
+    let body_future = body(unsafe { &*scope_ref });
+
+    LocalScopeFuture::new(body_future, scope)
+}
+
+/// Creates a new moro scope tied to the lifetime of a request: if
+/// `disconnect` resolves before the scope completes, the scope and all of its
+/// jobs are cancelled and `None` is returned.
+///
+/// This is a shorthand for `moro::scope(body).linked_to(disconnect)`; see
+/// [`ScopeBody::linked_to`] and the `request_scope` example.
+///
+/// # Examples
+///
+/// ```rust
+/// # futures::executor::block_on(async {
+/// let user = "alice";
+/// let response = moro::request_scope(futures::future::pending(), async |scope| {
+///     let profile = scope.spawn(async { format!("profile of {user}") });
+///     let posts = scope.spawn(async { 3 });
+///     format!("{} with {} posts", profile.await, posts.await)
+/// })
+/// .await;
+/// assert_eq!(response.as_deref(), Some("profile of alice with 3 posts"));
+/// # });
+/// ```
+///
+/// If the client disconnects first, the jobs still running are dropped:
+///
+/// ```rust
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// struct CountDrops<'a>(&'a AtomicUsize);
+///
+/// impl Drop for CountDrops<'_> {
+///     fn drop(&mut self) {
+///         self.0.fetch_add(1, Ordering::SeqCst);
+///     }
+/// }
+///
+/// # futures::executor::block_on(async {
+/// let dropped = AtomicUsize::new(0);
+/// let (hangup, disconnected) = futures::channel::oneshot::channel::<()>();
+/// let response = moro::request_scope(async { drop(disconnected.await) }, async |scope| {
+///     let profile = scope.spawn(async { "profile" }).await;
+///     let feed = scope.spawn(async {
+///         let _guard = CountDrops(&dropped);
+///         futures::future::pending::<&str>().await
+///     });
+///     // The client hangs up while the feed is loading.
+///     drop(hangup);
+///     format!("{profile} / {}", feed.await)
+/// })
+/// .await;
+/// assert_eq!(response, None);
+/// assert_eq!(dropped.load(Ordering::SeqCst), 1);
+/// # });
+/// ```
+pub async fn request_scope<'env, R, B>(disconnect: impl Future<Output = ()>, body: B) -> Option<R>
+where
+    R: Send + 'env,
+    for<'scope> B: async FnOnce(&'scope Scope<'scope, 'env, R>) -> R,
+{
+    scope(body).linked_to(disconnect).await
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use futures::Future;
+
+use crate::{adaptive::Adaptive, Scope, Spawned};
+
+/// Handle for spawning jobs into a scope with a cap on how many of them run
+/// at once. Created with [`Scope::with_concurrency_limit`].
+///
+/// Jobs spawned through the handle (or any of its clones) are queued in the
+/// scope, and start in order, once fewer than the limit of them are running.
+/// Jobs still queued when the scope is terminated are dropped without ever
+/// starting.
+pub struct LimitedScope<'scope, 'env: 'scope, R: Send + 'env> {
+    scope: &'scope Scope<'scope, 'env, R>,
+    limiter: Arc<Adaptive>,
+}
+
+impl<'scope, 'env, R: Send + 'env> LimitedScope<'scope, 'env, R> {
+    pub(crate) fn new(scope: &'scope Scope<'scope, 'env, R>, limit: usize) -> Self {
+        Self {
+            scope,
+            limiter: Arc::new(Adaptive::fixed(limit)),
+        }
+    }
+
+    /// Spawn a job that starts once a slot is free, like
+    /// [`Scope::spawn`] otherwise.
+    #[track_caller]
+    pub fn spawn<T>(
+        &self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send + 'scope>
+    where
+        T: 'scope + Send,
+    {
+        let limiter = self.limiter.clone();
+        self.scope.spawn(async move {
+            let _permit = limiter.acquire().await;
+            future.await
+        })
+    }
+
+    /// Maximum number of jobs of this handle that run at once.
+    pub fn limit(&self) -> usize {
+        self.limiter.limit()
+    }
+}
+
+impl<R: Send> Clone for LimitedScope<'_, '_, R> {
+    fn clone(&self) -> Self {
+        Self {
+            scope: self.scope,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
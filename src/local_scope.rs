@@ -0,0 +1,240 @@
+use std::{
+    cell::RefCell,
+    convert::Infallible,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use futures::{
+    channel::oneshot,
+    future::{AbortHandle, Abortable, LocalBoxFuture},
+    stream::FuturesUnordered,
+    Future, FutureExt,
+};
+use pin_project::pin_project;
+
+use crate::{body::Body, poll_jobs, Spawned};
+
+/// The future for a scope created with
+/// [`local_result_scope`][crate::local_result_scope].
+///
+/// Unlike [`ScopeBody`][crate::ScopeBody], the result of the body does not
+/// have to be `Send`, so a local scope can return e.g. `Rc`-based data. The
+/// scope itself can then not be terminated early, since the termination value
+/// would have to be shared with its jobs.
+#[pin_project]
+pub struct LocalResultScopeBody<'env, R, F>
+where
+    F: Future<Output = R>,
+{
+    #[pin]
+    body: Body<'env, 'env, Infallible, F>,
+}
+
+impl<'env, R, F> LocalResultScopeBody<'env, R, F>
+where
+    F: Future<Output = R>,
+{
+    pub(crate) fn new(body: Body<'env, 'env, Infallible, F>) -> Self {
+        Self { body }
+    }
+}
+
+impl<'env, R, F> Future for LocalResultScopeBody<'env, R, F>
+where
+    F: Future<Output = R>,
+{
+    type Output = R;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        self.project().body.poll_with(cx, |never| match never {})
+    }
+}
+
+/// A scope whose jobs run on the current thread, created with
+/// [`local_scope!`][crate::local_scope!].
+///
+/// Unlike [`Scope`][crate::Scope], neither the jobs nor the scope's result
+/// need to be `Send`, so they can share e.g. `Rc<RefCell<..>>` state. In
+/// exchange, the scope cannot be shared with other threads, and must be
+/// polled by a single-threaded executor (e.g. a GUI event loop, or wasm).
+pub struct LocalScope<'scope, 'env: 'scope, R: 'env> {
+    futures: RefCell<Pin<Box<FuturesUnordered<LocalBoxFuture<'scope, ()>>>>>,
+    /// Jobs spawned while `futures` is being polled.
+    enqueued: RefCell<Vec<LocalBoxFuture<'scope, ()>>>,
+    terminated: RefCell<Option<R>>,
+    phantom: PhantomData<&'scope &'env ()>,
+}
+
+impl<'scope, 'env, R> LocalScope<'scope, 'env, R> {
+    pub(crate) fn new() -> Rc<Self> {
+        Rc::new(Self {
+            futures: RefCell::new(Box::pin(FuturesUnordered::new())),
+            enqueued: Default::default(),
+            terminated: Default::default(),
+            phantom: Default::default(),
+        })
+    }
+
+    /// Spawn a job that will run concurrently with everything else in the
+    /// scope, like [`Scope::spawn`][crate::Scope::spawn] but without
+    /// requiring the job to be `Send`.
+    #[track_caller]
+    pub fn spawn<T>(
+        &'scope self,
+        future: impl Future<Output = T> + 'scope,
+    ) -> Spawned<impl Future<Output = T>>
+    where
+        T: 'scope,
+    {
+        let (abort, registration) = AbortHandle::new_pair();
+        let (tx, rx) = oneshot::channel();
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let job_finished = finished.clone();
+        self.enqueued.borrow_mut().push(Box::pin(async move {
+            if let Ok(v) = Abortable::new(future, registration).await {
+                let _ = tx.send(v);
+                job_finished.store(true, Ordering::Release);
+            }
+        }));
+
+        Spawned::for_job(
+            rx.map(|r| match r {
+                Ok(v) => v,
+                Err(e) => panic!("unexpected error: {e:?}"),
+            }),
+            abort,
+            finished,
+        )
+    }
+
+    /// Terminate the scope immediately with `value`, like
+    /// [`Scope::terminate`][crate::Scope::terminate].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # futures::executor::block_on(async {
+    /// let token = Rc::new(());
+    /// let result = moro::local_scope!(|scope| {
+    ///     let held = token.clone();
+    ///     scope.spawn(async move {
+    ///         let _held = held;
+    ///         futures::future::pending::<()>().await
+    ///     });
+    ///     scope.spawn(async { scope.terminate("stopped").await }).await
+    /// })
+    /// .await;
+    /// assert_eq!(result, "stopped");
+    /// // The pending job was dropped.
+    /// assert_eq!(Rc::strong_count(&token), 1);
+    /// # });
+    /// ```
+    pub fn terminate<T>(&'scope self, value: R) -> impl Future<Output = T> + 'scope
+    where
+        T: 'scope,
+    {
+        let mut terminated = self.terminated.borrow_mut();
+        if terminated.is_none() {
+            *terminated = Some(value);
+        }
+
+        // The scope stops polling us at our next await point.
+        futures::future::pending()
+    }
+
+    /// Polls the jobs; see `Scope::poll_jobs`.
+    fn poll_jobs(&self, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        let mut futures = self.futures.borrow_mut();
+        poll_jobs::poll_jobs(
+            || self.terminated.borrow_mut().take(),
+            || {
+                futures.extend(std::mem::take(&mut *self.enqueued.borrow_mut()));
+                poll_jobs::poll_unordered(futures.as_mut(), cx, || {
+                    self.terminated.borrow().is_some()
+                })
+            },
+            || self.enqueued.borrow().is_empty() && self.terminated.borrow().is_none(),
+        )
+    }
+
+    fn clear(&self) {
+        self.futures.borrow_mut().clear();
+        self.enqueued.borrow_mut().clear();
+    }
+}
+
+/// The future for a scope created with [`local_scope!`][crate::local_scope!].
+///
+/// # Unsafe contract
+///
+/// - `body_future` and `result` will be dropped BEFORE `scope`.
+pub struct LocalScopeFuture<'env, R: 'env> {
+    body_future: Option<LocalBoxFuture<'env, R>>,
+    result: Option<R>,
+    scope: Rc<LocalScope<'env, 'env, R>>,
+}
+
+impl<'env, R> LocalScopeFuture<'env, R> {
+    pub(crate) fn new(
+        body_future: LocalBoxFuture<'env, R>,
+        scope: Rc<LocalScope<'env, 'env, R>>,
+    ) -> Self {
+        Self {
+            body_future: Some(body_future),
+            result: None,
+            scope,
+        }
+    }
+}
+
+// The result is never pinned, and the body is boxed.
+impl<R> Unpin for LocalScopeFuture<'_, R> {}
+
+impl<R> Future for LocalScopeFuture<'_, R> {
+    type Output = R;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<R> {
+        let this = self.get_mut();
+
+        if let Some(body_future) = &mut this.body_future {
+            if let Poll::Ready(r) = body_future.as_mut().poll(cx) {
+                this.result = Some(r);
+                this.body_future = None;
+            }
+        }
+
+        match ready!(this.scope.poll_jobs(cx)) {
+            Some(v) => {
+                this.body_future = None;
+                this.scope.clear();
+                Poll::Ready(v)
+            }
+            None => match this.result.take() {
+                None => Poll::Pending,
+                Some(v) => Poll::Ready(v),
+            },
+        }
+    }
+}
+
+impl<R> Drop for LocalScopeFuture<'_, R> {
+    fn drop(&mut self) {
+        // Fulfill our unsafe contract and ensure we drop other fields
+        // before we drop scope.
+        self.body_future = None;
+        self.result = None;
+        self.scope.clear();
+    }
+}
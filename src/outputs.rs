@@ -0,0 +1,126 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    marker::PhantomData,
+    sync::Mutex,
+};
+
+use crate::AsyncIterator;
+
+/// Output channels of a scope, one per output type, see
+/// [`Scope::spawn_yielding`][crate::Scope::spawn_yielding].
+pub(crate) type OutputTable = Mutex<HashMap<TypeId, Box<dyn ErasedChannel>>>;
+
+/// An [`OutputChannel`] of any type.
+pub(crate) trait ErasedChannel: Send {
+    fn close(&self);
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// The channel of a scope's outputs of type `T`.
+///
+/// The channel lives as long as the scope, so that values stay buffered until
+/// they are taken, however late [`Scope::outputs`][crate::Scope::outputs] is
+/// called. It is closed once the scope has no jobs left, and replaced by a
+/// fresh channel if jobs yielding values of type `T` are spawned afterwards.
+struct OutputChannel<T> {
+    tx: async_channel::Sender<T>,
+    rx: async_channel::Receiver<T>,
+}
+
+impl<T: Send + 'static> OutputChannel<T> {
+    fn new() -> Self {
+        // Capacity 1: a yielding job cannot run ahead of the consumer.
+        let (tx, rx) = async_channel::bounded(1);
+        Self { tx, rx }
+    }
+}
+
+impl<T: Send + 'static> ErasedChannel for OutputChannel<T> {
+    fn close(&self) {
+        self.tx.close();
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn channel<T: Send + 'static>(
+    table: &mut HashMap<TypeId, Box<dyn ErasedChannel>>,
+) -> &mut OutputChannel<T> {
+    table
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| Box::new(OutputChannel::<T>::new()))
+        .as_any_mut()
+        .downcast_mut()
+        .unwrap()
+}
+
+pub(crate) fn yielder<'scope, T: Send + 'static>(table: &'scope OutputTable) -> Yielder<'scope, T> {
+    let mut table = table.lock().unwrap();
+    let channel = channel::<T>(&mut table);
+    if channel.tx.is_closed() {
+        // The scope ran out of jobs before; carry over the value that may
+        // still be buffered (at most one) into a fresh channel.
+        let fresh = OutputChannel::new();
+        if let Ok(value) = channel.rx.try_recv() {
+            let _ = fresh.tx.try_send(value);
+        }
+        *channel = fresh;
+    }
+    Yielder {
+        tx: channel.tx.clone(),
+        phantom: PhantomData,
+    }
+}
+
+pub(crate) fn outputs<T: Send + 'static>(table: &OutputTable) -> Outputs<T> {
+    Outputs {
+        rx: receiver(table),
+    }
+}
+
+pub(crate) fn receiver<T: Send + 'static>(table: &OutputTable) -> async_channel::Receiver<T> {
+    channel::<T>(&mut table.lock().unwrap()).rx.clone()
+}
+
+/// Closes every output channel, once the scope has no jobs left that could
+/// yield into them. Buffered values can still be received.
+pub(crate) fn close_all(table: &OutputTable) {
+    for channel in table.lock().unwrap().values() {
+        channel.close();
+    }
+}
+
+/// Handed to jobs spawned with [`Scope::spawn_yielding`][crate::Scope::spawn_yielding]
+/// to contribute values to the scope's [`outputs`][crate::Scope::outputs].
+pub struct Yielder<'scope, T: Send + 'static> {
+    tx: async_channel::Sender<T>,
+    phantom: PhantomData<&'scope ()>,
+}
+
+impl<T: Send + 'static> Yielder<'_, T> {
+    /// Pushes `value` to the scope's outputs, waiting while the consumer
+    /// has not yet taken the previous value.
+    pub async fn yield_(&self, value: T) {
+        // Sending only fails once the channel is closed, which happens when
+        // the scope has no jobs left, so not while this job is running.
+        let _ = self.tx.send(value).await;
+    }
+}
+
+/// The values yielded by jobs spawned with
+/// [`Scope::spawn_yielding`][crate::Scope::spawn_yielding]; see
+/// [`Scope::outputs`][crate::Scope::outputs].
+pub struct Outputs<T> {
+    rx: async_channel::Receiver<T>,
+}
+
+impl<T> AsyncIterator for Outputs<T> {
+    type Item = T;
+
+    async fn next(&mut self) -> Option<T> {
+        self.rx.recv().await.ok()
+    }
+}
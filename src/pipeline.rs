@@ -0,0 +1,133 @@
+use futures::Future;
+
+use crate::{AsyncIterator, Scope};
+
+/// A staged pipeline running inside a moro scope. See [`pipeline`] for details.
+pub struct Pipeline<'scope, 'env, R, T>
+where
+    R: Send + 'env,
+{
+    scope: &'scope Scope<'scope, 'env, R>,
+    receiver: async_channel::Receiver<T>,
+    prefetch: usize,
+}
+
+/// Creates a pipeline whose first stage yields the items of `source`.
+///
+/// Each stage added with [`Pipeline::stage`] runs as its own job in `scope`.
+/// Stages are connected by bounded channels that hold at most `prefetch`
+/// items, so a fast stage runs ahead of a slow one by at most `prefetch`
+/// items before it waits (backpressure). The items coming out of the final
+/// stage are consumed with [`AsyncIterator::next`].
+///
+/// # Panics
+///
+/// Panics if `prefetch` is zero.
+///
+/// # Examples
+///
+/// Stages run concurrently: the second stage starts on the first item
+/// before the first stage has seen the last one.
+///
+/// ```rust
+/// # use std::sync::Mutex;
+/// # use moro::AsyncIterator;
+/// # futures::executor::block_on(async {
+/// let log = Mutex::new(vec![]);
+/// let log = &log;
+/// let result = moro::async_scope!(|scope| {
+///     let mut output = moro::pipeline(scope, 0..4, 1)
+///         .stage(move |i| async move {
+///             log.lock().unwrap().push(("double", i));
+///             i * 2
+///         })
+///         .stage(move |i| async move {
+///             log.lock().unwrap().push(("format", i / 2));
+///             format!("<{i}>")
+///         });
+///     let mut result = vec![];
+///     while let Some(s) = output.next().await {
+///         result.push(s);
+///     }
+///     result
+/// })
+/// .await;
+/// assert_eq!(result, ["<0>", "<2>", "<4>", "<6>"]);
+///
+/// let log = log.lock().unwrap();
+/// let first_formatted = log.iter().position(|e| *e == ("format", 0)).unwrap();
+/// let last_doubled = log.iter().position(|e| *e == ("double", 3)).unwrap();
+/// assert!(first_formatted < last_doubled);
+/// # });
+/// ```
+#[track_caller]
+pub fn pipeline<'scope, 'env, R, T>(
+    scope: &'scope Scope<'scope, 'env, R>,
+    source: impl IntoIterator<Item = T, IntoIter: Send + 'scope>,
+    prefetch: usize,
+) -> Pipeline<'scope, 'env, R, T>
+where
+    R: Send + 'env,
+    T: Send + 'scope,
+{
+    let (tx, rx) = async_channel::bounded(prefetch);
+    let source = source.into_iter();
+    drop(scope.spawn(async move {
+        for item in source {
+            if tx.send(item).await.is_err() {
+                break;
+            }
+        }
+    }));
+    Pipeline {
+        scope,
+        receiver: rx,
+        prefetch,
+    }
+}
+
+impl<'scope, 'env, R, T> Pipeline<'scope, 'env, R, T>
+where
+    R: Send + 'env,
+    T: Send + 'scope,
+{
+    /// Appends a stage that transforms each item with `op`.
+    ///
+    /// The stage is spawned as a job in the pipeline's scope right away and
+    /// processes items one at a time, in order.
+    #[track_caller]
+    pub fn stage<U, Fut>(
+        self,
+        mut op: impl FnMut(T) -> Fut + Send + 'scope,
+    ) -> Pipeline<'scope, 'env, R, U>
+    where
+        U: Send + 'scope,
+        Fut: Future<Output = U> + Send + 'scope,
+    {
+        let (tx, rx) = async_channel::bounded(self.prefetch);
+        let input = self.receiver;
+        drop(self.scope.spawn(async move {
+            while let Ok(item) = input.recv().await {
+                if tx.send(op(item).await).await.is_err() {
+                    break;
+                }
+            }
+        }));
+        Pipeline {
+            scope: self.scope,
+            receiver: rx,
+            prefetch: self.prefetch,
+        }
+    }
+}
+
+impl<R, T> AsyncIterator for Pipeline<'_, '_, R, T>
+where
+    R: Send,
+{
+    type Item = T;
+
+    async fn next(&mut self) -> Option<T> {
+        self.receiver.recv().await.ok()
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{stream::FuturesUnordered, Future, Stream};
+
+/// The loop polling a scope's jobs, shared by [`Scope`][crate::Scope] and
+/// [`LocalScope`][crate::LocalScope].
+///
+/// - `take_terminated` returns the termination value, once the scope is
+///   terminated and may finish with it;
+/// - `poll_running` moves newly spawned jobs into the running ones and polls
+///   them, returning `Some(true)` if none is left, `Some(false)` if some are
+///   pending, and `None` if the scope was terminated meanwhile;
+/// - `settled` is true if no jobs were spawned and the scope was not
+///   terminated since `poll_running` returned.
+///
+/// Returns `Ready(None)` once all jobs are done.
+pub(crate) fn poll_jobs<R>(
+    mut take_terminated: impl FnMut() -> Option<R>,
+    mut poll_running: impl FnMut() -> Option<bool>,
+    mut settled: impl FnMut() -> bool,
+) -> Poll<Option<R>> {
+    loop {
+        // once we are terminated, we do no more work.
+        if let Some(r) = take_terminated() {
+            return Poll::Ready(Some(r));
+        }
+
+        let Some(all_done) = poll_running() else {
+            continue;
+        };
+
+        // Jobs spawned while we were polling (including by `terminate`)
+        // have not been polled yet, and a pending job may have terminated
+        // the scope, so go around again.
+        if settled() {
+            return if all_done {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            };
+        }
+    }
+}
+
+/// Polls `futures` until all are pending or done, for `poll_running` in
+/// [`poll_jobs`]; stops early, returning `None`, once `terminating`.
+pub(crate) fn poll_unordered<F: Future<Output = ()>>(
+    mut futures: Pin<&mut FuturesUnordered<F>>,
+    cx: &mut Context<'_>,
+    terminating: impl Fn() -> bool,
+) -> Option<bool> {
+    loop {
+        match futures.as_mut().poll_next(cx) {
+            Poll::Ready(Some(())) => {
+                // once we are terminated, we do no more work.
+                if terminating() {
+                    return None;
+                }
+            }
+            Poll::Ready(None) => return Some(true),
+            Poll::Pending => return Some(false),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Token bucket shared by the jobs spawned with
+/// [`Scope::spawn_rate_limited`][crate::Scope::spawn_rate_limited]; jobs are
+/// not limited until it is configured.
+#[derive(Default)]
+pub(crate) struct RateLimiter {
+    bucket: Mutex<Option<Bucket>>,
+}
+
+struct Bucket {
+    /// Tokens added per second.
+    rate: f64,
+
+    /// Maximum number of tokens.
+    burst: f64,
+
+    /// Available tokens as of `updated`; negative when tokens are reserved
+    /// by jobs that are waiting.
+    tokens: f64,
+
+    /// Unset until the first reservation, since the scope's clock may be
+    /// replaced after the bucket is configured.
+    updated: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Replaces the bucket with a full one with the given parameters.
+    pub(crate) fn configure(&self, rate: f64, burst: usize) {
+        assert!(
+            rate > 0.0 && rate.is_finite(),
+            "rate limit must be positive and finite"
+        );
+        let burst = burst as f64;
+        *self.bucket.lock().unwrap() = Some(Bucket {
+            rate,
+            burst,
+            tokens: burst,
+            updated: None,
+        });
+    }
+
+    /// Reserves a token at time `now` and returns when the reserving job may
+    /// start.
+    pub(crate) fn reserve(&self, now: Instant) -> Instant {
+        let mut bucket = self.bucket.lock().unwrap();
+        let Some(bucket) = bucket.as_mut() else {
+            return now;
+        };
+
+        let updated = *bucket.updated.get_or_insert(now);
+        let elapsed = now.saturating_duration_since(updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * bucket.rate).min(bucket.burst);
+        bucket.updated = Some(updated.max(now));
+
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            now
+        } else {
+            now + Duration::from_secs_f64(-bucket.tokens / bucket.rate)
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use futures::future::BoxFuture;
+
+/// A job spawned with [`Scope::spawn_restartable`][crate::Scope::spawn_restartable]
+/// that did not finish before the deadline given to
+/// [`ScopeBody::drain_or_recover`][crate::ScopeBody::drain_or_recover].
+///
+/// The job can be persisted and later started afresh with [`start`][Self::start],
+/// for example by spawning it into a new scope.
+#[derive(Clone)]
+pub struct PendingJob<'env> {
+    make: Arc<dyn Fn() -> BoxFuture<'env, ()> + Send + Sync + 'env>,
+}
+
+impl<'env> PendingJob<'env> {
+    pub(crate) fn new(make: Arc<dyn Fn() -> BoxFuture<'env, ()> + Send + Sync + 'env>) -> Self {
+        Self { make }
+    }
+
+    /// Creates a new future running the job from the beginning.
+    pub fn start(&self) -> BoxFuture<'env, ()> {
+        (self.make)()
+    }
+}
+
+/// The restartable jobs of a scope that have not completed yet.
+#[derive(Default)]
+pub(crate) struct RestartTable<'env> {
+    next_key: u64,
+    jobs: BTreeMap<u64, PendingJob<'env>>,
+}
+
+impl<'env> RestartTable<'env> {
+    pub(crate) fn insert(&mut self, job: PendingJob<'env>) -> u64 {
+        let key = self.next_key;
+        self.next_key += 1;
+        self.jobs.insert(key, job);
+        key
+    }
+
+    pub(crate) fn remove(&mut self, key: u64) {
+        self.jobs.remove(&key);
+    }
+
+    /// Removes and returns the unfinished jobs, in the order they were spawned.
+    pub(crate) fn take(&mut self) -> Vec<PendingJob<'env>> {
+        std::mem::take(&mut self.jobs).into_values().collect()
+    }
+}
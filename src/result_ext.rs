@@ -12,6 +12,18 @@ pub trait UnwrapOrCancel: Send + Sized {
     where
         T: Send,
         Self: 'env;
+
+    /// Like `unwrap_or_cancel`, but converts the error into the scope's
+    /// error type with [`Into`], like the `?` operator does.
+    async fn unwrap_or_cancel_into<'scope, 'env, T, E>(
+        self,
+        scope: &'scope Scope<'scope, 'env, Result<T, E>>,
+    ) -> Self::Ok
+    where
+        T: Send,
+        E: Send,
+        Self::Err: Into<E>,
+        Self: 'env;
 }
 
 #[async_trait::async_trait]
@@ -36,4 +48,20 @@ where
             Err(e) => scope.terminate(Err(e)).await,
         }
     }
+
+    async fn unwrap_or_cancel_into<'scope, 'env, T, E2>(
+        self,
+        scope: &'scope Scope<'scope, 'env, Result<T, E2>>,
+    ) -> O
+    where
+        T: Send,
+        E2: Send,
+        E: Into<E2>,
+        Self: 'env,
+    {
+        match self {
+            Ok(o) => o,
+            Err(e) => scope.terminate_with(Err(e.into())).await,
+        }
+    }
 }
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+/// How [`Scope::spawn_retry`][crate::Scope::spawn_retry] spaces out the
+/// attempts of a job.
+///
+/// The delays grow exponentially: the delay after the `n`th failed attempt
+/// (counting from zero) is `base * 2^n`, capped at a maximum (60 seconds by
+/// default). [`Jitter`] randomizes the delays, using a generator seeded with
+/// [`with_seed`][Self::with_seed], so that the delays are reproducible.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::time::Duration;
+/// use moro::{Jitter, RetryPolicy};
+///
+/// let ms = Duration::from_millis;
+/// let policy = RetryPolicy::new(5, ms(100)).with_max_delay(ms(500));
+/// let delays: Vec<_> = policy.delays().collect();
+/// assert_eq!(delays, [ms(100), ms(200), ms(400), ms(500)]);
+///
+/// // Full jitter: uniformly random between zero and the exponential delay.
+/// let full: Vec<_> = policy.clone().with_jitter(Jitter::Full).delays().collect();
+/// for (delay, max) in full.iter().zip(&delays) {
+///     assert!(delay <= max);
+/// }
+///
+/// // Equal jitter: at least half the exponential delay.
+/// let equal: Vec<_> = policy.clone().with_jitter(Jitter::Equal).delays().collect();
+/// for (delay, max) in equal.iter().zip(&delays) {
+///     assert!(*delay >= *max / 2 && delay <= max);
+/// }
+///
+/// // Decorrelated jitter: between `base` and three times the previous delay.
+/// let decorrelated: Vec<_> = policy.clone().with_jitter(Jitter::Decorrelated).delays().collect();
+/// let mut previous = ms(100);
+/// for delay in &decorrelated {
+///     assert!(*delay >= ms(100) && *delay <= (previous * 3).min(ms(500)));
+///     previous = *delay;
+/// }
+///
+/// // The same seed gives the same delays; a different seed different ones.
+/// let seeded = |seed| policy.clone().with_jitter(Jitter::Full).with_seed(seed);
+/// assert_eq!(seeded(1).delays().collect::<Vec<_>>(), seeded(1).delays().collect::<Vec<_>>());
+/// assert_ne!(seeded(1).delays().collect::<Vec<_>>(), seeded(2).delays().collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base: Duration,
+    max_delay: Duration,
+    jitter: Jitter,
+    seed: u64,
+}
+
+/// Randomization of the delays of a [`RetryPolicy`].
+///
+/// Without jitter, jobs that failed at the same time (say, because a server
+/// was briefly unavailable) retry at the same times, and keep overloading
+/// the server together. Jitter spreads their attempts out.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Jitter {
+    /// The exponential delay, unchanged.
+    #[default]
+    None,
+
+    /// A uniformly random delay between zero and the exponential delay.
+    Full,
+
+    /// Half the exponential delay, plus a uniformly random delay of up to
+    /// the other half.
+    Equal,
+
+    /// A uniformly random delay between the base delay and three times the
+    /// previous delay, capped at the maximum delay.
+    Decorrelated,
+}
+
+impl RetryPolicy {
+    /// Makes up to `max_attempts` attempts, waiting `base * 2^n` after the
+    /// `n`th failed attempt.
+    pub fn new(max_attempts: u32, base: Duration) -> Self {
+        Self {
+            max_attempts,
+            base,
+            max_delay: Duration::from_secs(60),
+            jitter: Jitter::None,
+            seed: 0,
+        }
+    }
+
+    /// Caps the delay between two attempts.
+    pub fn with_max_delay(self, max_delay: Duration) -> Self {
+        Self { max_delay, ..self }
+    }
+
+    /// Randomizes the delays, see [`Jitter`].
+    pub fn with_jitter(self, jitter: Jitter) -> Self {
+        Self { jitter, ..self }
+    }
+
+    /// Seeds the random generator used for [`Jitter`].
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Maximum number of attempts.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// The delays between consecutive attempts; one fewer than the maximum
+    /// number of attempts.
+    pub fn delays(&self) -> impl Iterator<Item = Duration> + Send + 'static {
+        let policy = self.clone();
+        // Spread the seed's bits; xorshift gets stuck at zero.
+        let mut state = policy
+            .seed
+            .wrapping_add(1)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+            .max(1);
+        let mut random = move |max: Duration| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            max.mul_f64((state >> 11) as f64 / (1u64 << 53) as f64)
+        };
+        let mut previous = policy.base;
+        (0..policy.max_attempts.saturating_sub(1)).map(move |n| {
+            let exponential = policy
+                .base
+                .saturating_mul(2u32.saturating_pow(n))
+                .min(policy.max_delay);
+            let delay = match policy.jitter {
+                Jitter::None => exponential,
+                Jitter::Full => random(exponential),
+                Jitter::Equal => exponential / 2 + random(exponential - exponential / 2),
+                Jitter::Decorrelated => {
+                    let upper = previous.saturating_mul(3).max(policy.base);
+                    (policy.base + random(upper - policy.base)).min(policy.max_delay)
+                }
+            };
+            previous = delay;
+            delay
+        })
+    }
+}
@@ -1,13 +1,48 @@
 use std::{
+    any::Any,
+    borrow::Cow,
+    cmp::Reverse,
+    collections::HashSet,
+    fmt::Write,
+    hash::Hash,
     marker::PhantomData,
+    panic::{AssertUnwindSafe, Location},
     pin::Pin,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
     task::Poll,
+    time::Duration,
 };
 
-use futures::{future::BoxFuture, stream::FuturesUnordered, Future, Stream};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{AbortHandle, Abortable, BoxFuture, Either, Shared},
+    stream::FuturesUnordered,
+    task::waker_ref,
+    Future, FutureExt, SinkExt, StreamExt,
+};
 
-use crate::Spawned;
+use crate::{
+    adaptive::Adaptive,
+    async_iter::AsyncIterator,
+    broadcast,
+    cancellation::{Cancellation, CancellationToken},
+    context::Contexts,
+    events::{Events, ScopeEvent},
+    job::{JobEntry, JobGuard, JobHandle, JobInfo, JobTable},
+    job_set::JobSet,
+    limited::LimitedScope,
+    outputs::{self, OutputTable, Outputs, Yielder},
+    poll_jobs,
+    rate_limit::RateLimiter,
+    restart::{PendingJob, RestartTable},
+    self_limit::SelfLimit,
+    waker::CoalescingWaker,
+    AdaptiveConfig, Borrows, BroadcastReceiver, BroadcastSender, CancelOnDrop, Clock, RetryPolicy,
+    SharedHandle, Spawned, SystemClock, Timed,
+};
 
 /// Represents a moro "async scope". See the [`async_scope`][crate::async_scope] macro for details.
 pub struct Scope<'scope, 'env: 'scope, R: Send + 'env> {
@@ -19,7 +54,56 @@ pub struct Scope<'scope, 'env: 'scope, R: Send + 'env> {
     /// CONCURRENTLY and hence there will be no contention.
     futures: Mutex<Pin<Box<FuturesUnordered<BoxFuture<'scope, ()>>>>>,
     enqueued: Mutex<Vec<BoxFuture<'scope, ()>>>,
+    /// If set, jobs are stored in `ordered` rather than `futures`, and are
+    /// always polled in the order they were spawned. See [`crate::scope_fn_biased`].
+    biased: AtomicBool,
+    /// If set, biased polling visits the jobs in a pseudo-random order
+    /// derived from this state instead. See `scope_fn_with_seed`.
+    shuffle: Mutex<Option<u64>>,
+    ordered: Mutex<Vec<BoxFuture<'scope, ()>>>,
     terminated: Mutex<Option<R>>,
+    /// If set, the jobs still running when the body returns are dropped.
+    /// See [`Self::detach_remaining`].
+    detach_remaining: AtomicBool,
+    /// Signaled on termination, see [`Self::cancellation_token`].
+    cancellation: Cancellation,
+    /// Number of jobs spawned with [`Self::spawn_graceful`] that have not
+    /// completed; once terminated, the scope waits for them.
+    graceful: AtomicUsize,
+    /// Every job that has been spawned and has not yet completed.
+    jobs: JobTable,
+    next_job_id: AtomicU64,
+    /// Maximum number of prioritized jobs in flight, see [`Self::spawn_prioritized`].
+    budget: Mutex<Option<usize>>,
+    /// Channels for the values yielded by jobs, see [`Self::spawn_yielding`].
+    outputs: OutputTable,
+    /// Completion position expected of the next job spawned with
+    /// [`Self::spawn_ordered_debug`] to complete.
+    next_ordered: AtomicUsize,
+    /// Jobs spawned with [`Self::spawn_restartable`] that have not completed.
+    ///
+    /// These outlive `'env`, but storing them as such would make `Scope`
+    /// invariant in `'env`; `ScopeBody` sees them as `'env` because its
+    /// scope has `'scope == 'env`.
+    restartable: Mutex<RestartTable<'scope>>,
+    events: Events,
+    /// Admission control for [`Self::spawn_adaptive`].
+    adaptive: Adaptive,
+    /// If set, the body is held back while too many jobs are in flight.
+    /// See [`crate::scope_fn_self_limiting`].
+    self_limit: Mutex<Option<SelfLimit>>,
+    /// Token bucket for [`Self::spawn_rate_limited`].
+    rate_limiter: RateLimiter,
+    /// Waker handed to the jobs; coalesces their wakeups into a single
+    /// wakeup of the task polling the scope.
+    waker: Arc<CoalescingWaker>,
+    clock: Mutex<Arc<dyn Clock>>,
+    /// Dropped once no job will run anymore, resolving `completed`.
+    /// See [`crate::ScopeBody::completion`].
+    complete: Mutex<Option<oneshot::Sender<()>>>,
+    completed: Shared<oneshot::Receiver<()>>,
+    /// Request-scoped values, see [`Self::context`].
+    contexts: Contexts,
     phantom: PhantomData<&'scope &'env ()>,
 }
 
@@ -30,10 +114,33 @@ fn is_sync<T: Sync>(t: T) -> T {
 impl<'scope, 'env, R: Send> Scope<'scope, 'env, R> {
     /// Create a scope.
     pub(crate) fn new() -> Arc<Self> {
+        let (complete, completed) = oneshot::channel();
+        let waker = CoalescingWaker::new();
         Arc::new(is_sync(Self {
             futures: Mutex::new(Box::pin(FuturesUnordered::new())),
             enqueued: Default::default(),
+            biased: Default::default(),
+            shuffle: Default::default(),
+            ordered: Default::default(),
             terminated: Default::default(),
+            detach_remaining: Default::default(),
+            cancellation: Cancellation::new(),
+            graceful: Default::default(),
+            jobs: Default::default(),
+            next_job_id: Default::default(),
+            budget: Default::default(),
+            outputs: Default::default(),
+            next_ordered: Default::default(),
+            restartable: Default::default(),
+            events: Default::default(),
+            adaptive: Default::default(),
+            self_limit: Default::default(),
+            rate_limiter: Default::default(),
+            waker,
+            clock: Mutex::new(Arc::new(SystemClock)),
+            complete: Mutex::new(Some(complete)),
+            completed: completed.shared(),
+            contexts: Default::default(),
             phantom: Default::default(),
         }))
     }
@@ -49,28 +156,153 @@ impl<'scope, 'env, R: Send> Scope<'scope, 'env, R> {
     /// It is ok to invoke it again after `Ready(Ok(()))` has been returned;
     /// if any new jobs have been spawned, they will execute.
     pub(crate) fn poll_jobs(&self, cx: &mut std::task::Context<'_>) -> Poll<Option<R>> {
-        let mut futures = self.futures.lock().unwrap();
-        'outer: loop {
-            // once we are terminated, we do no more work.
-            if let Some(r) = self.terminated.lock().unwrap().take() {
-                return Poll::Ready(Some(r));
-            }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("moro::poll_jobs").entered();
+        self.waker.register(cx.waker());
+        let waker = waker_ref(&self.waker);
+        let cx = &mut std::task::Context::from_waker(&waker);
 
-            futures.extend(self.enqueued.lock().unwrap().drain(..));
+        let biased = self.biased.load(Ordering::Relaxed);
+        let mut futures = self.futures.lock().unwrap();
+        let mut ordered = self.ordered.lock().unwrap();
+        let poll = poll_jobs::poll_jobs(
+            || self.take_terminated(),
+            || {
+                let enqueued = std::mem::take(&mut *self.enqueued.lock().unwrap());
+                if !biased {
+                    futures.extend(enqueued);
+                    return poll_jobs::poll_unordered(futures.as_mut(), cx, || self.terminating());
+                }
+                ordered.extend(enqueued);
+                self.shuffle(&mut ordered);
+                let mut i = 0;
+                while i < ordered.len() {
+                    if ordered[i].as_mut().poll(cx).is_ready() {
+                        drop(ordered.remove(i));
 
-            while let Some(()) = ready!(futures.as_mut().poll_next(cx)) {
-                // once we are terminated, we do no more work.
-                if self.terminated.lock().unwrap().is_some() {
-                    continue 'outer;
+                        // once we are terminated, we do no more work.
+                        if self.terminating() {
+                            return None;
+                        }
+                    } else {
+                        i += 1;
+                    }
                 }
-            }
+                Some(ordered.is_empty())
+            },
+            || self.enqueued.lock().unwrap().is_empty() && !self.terminating(),
+        );
+        if let Poll::Ready(None) = poll {
+            // No job is left to yield outputs.
+            outputs::close_all(&self.outputs);
+        }
+        poll
+    }
+
+    /// True if the scope was terminated and no job is shutting down
+    /// gracefully, see [`Self::spawn_graceful`].
+    fn terminating(&self) -> bool {
+        self.is_terminated() && self.graceful.load(Ordering::Acquire) == 0
+    }
+
+    /// Takes the termination value if [`Self::terminating`].
+    fn take_terminated(&self) -> Option<R> {
+        let mut terminated = self.terminated.lock().unwrap();
+        if self.graceful.load(Ordering::Acquire) == 0 {
+            terminated.take()
+        } else {
+            None
+        }
+    }
+
+    /// True once the scope was terminated, even while graceful jobs are
+    /// still shutting down.
+    pub(crate) fn is_terminated(&self) -> bool {
+        self.terminated.lock().unwrap().is_some()
+    }
+
+    /// Makes the scope poll its jobs in the order they were spawned.
+    /// Must be called before any job is spawned.
+    pub(crate) fn set_biased(&self) {
+        self.biased.store(true, Ordering::Relaxed);
+    }
+
+    /// Holds back the body while too many jobs are in flight, see
+    /// `scope_fn_self_limiting`.
+    pub(crate) fn set_self_limiting(&self) {
+        *self.self_limit.lock().unwrap() = Some(SelfLimit::new());
+    }
 
-            if self.enqueued.lock().unwrap().is_empty() {
-                return Poll::Ready(None);
+    /// Whether the body may be polled; false while a self-limiting scope
+    /// has too many jobs in flight.
+    pub(crate) fn admits_body(&self) -> bool {
+        match &mut *self.self_limit.lock().unwrap() {
+            Some(self_limit) => {
+                let spawned = self.next_job_id.load(Ordering::Relaxed);
+                let in_flight = self.jobs.lock().unwrap().len();
+                self_limit.admits(spawned, in_flight)
             }
+            None => true,
+        }
+    }
+
+    /// Polls the jobs in an order determined by `seed`, see `scope_fn_with_seed`.
+    #[cfg(feature = "fuzz-sched")]
+    pub(crate) fn set_seed(&self, seed: u64) {
+        self.set_biased();
+        // Spread the seed's bits; xorshift gets stuck at zero.
+        let state = seed.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        *self.shuffle.lock().unwrap() = Some(state.max(1));
+    }
+
+    /// Shuffles `jobs` with a xorshift generator, if a seed was set.
+    fn shuffle(&self, jobs: &mut [BoxFuture<'scope, ()>]) {
+        let mut shuffle = self.shuffle.lock().unwrap();
+        let Some(state) = &mut *shuffle else {
+            return;
+        };
+        for i in (1..jobs.len()).rev() {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            jobs.swap(i, (*state % (i as u64 + 1)) as usize);
         }
     }
 
+    pub(crate) fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.clock.lock().unwrap() = clock;
+    }
+
+    pub(crate) fn contexts(&self) -> &Contexts {
+        &self.contexts
+    }
+
+    pub(crate) fn output_table(&self) -> &OutputTable {
+        &self.outputs
+    }
+
+    /// Returns the scope's clock.
+    pub(crate) fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set_budget(&self, budget: usize) {
+        *self.budget.lock().unwrap() = Some(budget);
+    }
+
+    pub(crate) fn set_adaptive(&self, config: AdaptiveConfig) {
+        self.adaptive.configure(config);
+    }
+
+    pub(crate) fn set_rate_limit(&self, rate: f64, burst: usize) {
+        self.rate_limiter.configure(rate, burst);
+    }
+
+    /// Removes and returns the restartable jobs that have not completed.
+    pub(crate) fn take_restartable(&self) -> Vec<PendingJob<'scope>> {
+        self.restartable.lock().unwrap().take()
+    }
+
     /// Clear out all pending jobs. This is used when dropping the
     /// scope body to ensure that any possible references to `Scope`
     /// are removed before we drop it.
@@ -79,8 +311,36 @@ impl<'scope, 'env, R: Send> Scope<'scope, 'env, R> {
     ///
     /// Once this returns, there are no more pending tasks.
     pub(crate) fn clear(&self) {
-        self.futures.lock().unwrap().clear();
-        self.enqueued.lock().unwrap().clear();
+        // A job that panicked while being polled poisoned these, and we are
+        // likely being called while unwinding from that panic.
+        self.futures
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.ordered
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.enqueued
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.set_complete();
+    }
+
+    /// True if [`Self::detach_remaining`] was called.
+    pub(crate) fn detaches_remaining(&self) -> bool {
+        self.detach_remaining.load(Ordering::Relaxed)
+    }
+
+    /// Records that no job of the scope will run anymore.
+    pub(crate) fn set_complete(&self) {
+        self.complete.lock().unwrap().take();
+    }
+
+    /// Resolves once [`Self::set_complete`] was called.
+    pub(crate) fn completed(&self) -> impl Future<Output = ()> + Send + Sync + 'static {
+        self.completed.clone().map(drop)
     }
 
     /// Terminate the scope immediately -- all existing jobs will stop at their next await point
@@ -110,23 +370,371 @@ impl<'scope, 'env, R: Send> Scope<'scope, 'env, R> {
     /// assert_eq!(result, "cancellation-value");
     /// # });
     /// ```
+    ///
+    /// Jobs are dropped as soon as the scope completes, even if the scope
+    /// itself is kept around, so e.g. locks they hold are released:
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let connection = futures::lock::Mutex::new(vec![]);
+    ///
+    /// // A scope terminated while a job holds the lock.
+    /// let first = moro::async_scope!(|scope| {
+    ///     let (locked_tx, locked_rx) = futures::channel::oneshot::channel();
+    ///     scope.spawn(async {
+    ///         let mut connection = connection.lock().await;
+    ///         connection.push("first");
+    ///         locked_tx.send(()).unwrap();
+    ///         futures::future::pending::<()>().await;
+    ///     });
+    ///     locked_rx.await.unwrap();
+    ///     scope.terminate("terminated").await
+    /// });
+    /// let mut first = std::pin::pin!(first);
+    /// assert_eq!(first.as_mut().await, "terminated");
+    ///
+    /// // The first scope still exists, but no longer holds the lock.
+    /// moro::async_scope!(|scope| {
+    ///     scope
+    ///         .spawn(async {
+    ///             connection.lock().await.push("second");
+    ///         })
+    ///         .await
+    /// })
+    /// .await;
+    /// assert_eq!(*connection.try_lock().unwrap(), ["first", "second"]);
+    /// # });
+    /// ```
     pub fn terminate<T>(&'scope self, value: R) -> impl Future<Output = T> + 'scope
     where
         T: 'scope + Send,
     {
+        self.set_terminated(value);
+
+        // The scope stops polling us at our next await point.
+        futures::future::pending()
+    }
+
+    /// Like [`terminate`][Self::terminate], but converts `value` into the
+    /// scope's result type with [`Into`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result: String = moro::async_scope!(|scope| {
+    ///     scope.terminate_with("stopped early").await
+    /// })
+    /// .await;
+    /// assert_eq!(result, "stopped early");
+    /// # });
+    /// ```
+    pub fn terminate_with<T>(&'scope self, value: impl Into<R>) -> impl Future<Output = T> + 'scope
+    where
+        T: 'scope + Send,
+    {
+        self.terminate(value.into())
+    }
+
+    /// Races `jobs`: spawns each of them, and terminates the scope with the
+    /// output of the first to complete, cancelling the others.
+    ///
+    /// Like [`terminate`][Self::terminate], this returns a future that never
+    /// completes; await it so that the body stops there. The losing jobs,
+    /// and every other job in the scope, are dropped at their next `.await`,
+    /// within the scope, so whatever they borrow is released before the
+    /// scope completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `jobs` is empty, since the scope would never complete.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use futures::FutureExt;
+    /// # futures::executor::block_on(async {
+    /// let server = moro::async_scope!(|scope| {
+    ///     let primary = async { futures::future::pending::<&str>().await };
+    ///     let fallback = async { "fallback" };
+    ///     scope.race([primary.boxed(), fallback.boxed()]).await
+    /// })
+    /// .await;
+    /// assert_eq!(server, "fallback");
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn race<T, J>(
+        &'scope self,
+        jobs: impl IntoIterator<Item = J>,
+    ) -> impl Future<Output = T> + 'scope
+    where
+        T: 'scope + Send,
+        J: Future<Output = R> + Send + 'scope,
+    {
+        let mut racing = 0;
+        for job in jobs {
+            racing += 1;
+            drop(self.spawn(async move {
+                let winner = job.await;
+                self.terminate::<()>(winner).await
+            }));
+        }
+        assert!(racing > 0, "cannot race an empty set of jobs");
+        futures::future::pending()
+    }
+
+    /// Lets the body decide when the scope completes: once the body returns,
+    /// the jobs still running are abandoned rather than awaited, and the
+    /// scope completes with the body's result right away.
+    ///
+    /// By default, a scope waits for every job to complete after the body
+    /// returns. Since jobs may borrow from the scope's environment, they
+    /// cannot outlive the scope: abandoned jobs are dropped at their next
+    /// `.await`, like jobs of a terminated scope. Unlike
+    /// [`terminate`][Self::terminate], this does not take effect until the
+    /// body returns, and the result is whatever the body returns.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     scope.detach_remaining();
+    ///     // Never completes; dropped once the body returns.
+    ///     scope.spawn(futures::future::pending::<()>());
+    ///     scope.spawn(async { 22 }).await
+    /// })
+    /// .await;
+    /// assert_eq!(result, 22);
+    /// # });
+    /// ```
+    pub fn detach_remaining(&self) {
+        self.detach_remaining.store(true, Ordering::Relaxed);
+    }
+
+    /// Alias for [`terminate`][Self::terminate].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result: Result<u32, &str> = moro::async_scope!(|scope| {
+    ///     let value = scope.spawn(async { 22 }).await;
+    ///     if value > 10 {
+    ///         scope.cancel(Err("too large")).await
+    ///     }
+    ///     Ok(value)
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err("too large"));
+    /// # });
+    /// ```
+    pub fn cancel<T>(&'scope self, value: R) -> impl Future<Output = T> + 'scope
+    where
+        T: 'scope + Send,
+    {
+        self.terminate(value)
+    }
+
+    /// Returns a token through which the termination of the scope can be
+    /// observed; see [`CancellationToken`].
+    ///
+    /// Jobs that need to run shutdown code are best spawned with
+    /// [`spawn_graceful`][Self::spawn_graceful], which hands them a token.
+    /// Other jobs are dropped when the scope is terminated, but code that
+    /// cannot be dropped, such as a closure run by
+    /// [`spawn_blocking`][Self::spawn_blocking], can check the token to stop
+    /// early. Holding a token never delays the scope.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     let token = scope.cancellation_token();
+    ///     scope.spawn_blocking(move || {
+    ///         while !token.is_cancelled() {
+    ///             std::thread::sleep(Duration::from_millis(1));
+    ///         }
+    ///     });
+    ///     let _held_by_body = scope.cancellation_token();
+    ///     scope.terminate("stop").await
+    /// })
+    /// .await;
+    /// assert_eq!(result, "stop");
+    /// # });
+    /// ```
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.token()
+    }
+
+    /// Spawn a job that gets to shut down gracefully when the scope is
+    /// terminated.
+    ///
+    /// `job` is invoked right away with a [`CancellationToken`], whose
+    /// [`cancelled`][CancellationToken::cancelled] resolves once the scope is
+    /// terminated. When that happens, the body and the other jobs are dropped
+    /// at their next await point, as usual, but this job keeps running for
+    /// up to `grace`, measured with the scope's [`Clock`], e.g. to flush a
+    /// buffer; the scope completes once it is done. A job still running when
+    /// `grace` has elapsed is dropped, and its handle yields `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::{sync::Mutex, time::Duration};
+    /// # futures::executor::block_on(async {
+    /// let flushed = Mutex::new(vec![]);
+    /// let flushed = &flushed;
+    /// let result = moro::async_scope!(|scope| {
+    ///     scope.spawn_graceful(Duration::from_secs(5), |token| async move {
+    ///         let mut buffer = vec!["a", "b"];
+    ///         // Runs until the scope is terminated...
+    ///         token.cancelled().await;
+    ///         // ...and then gets to flush its buffer.
+    ///         buffer.push("last");
+    ///         flushed.lock().unwrap().append(&mut buffer);
+    ///     });
+    ///     scope.spawn(async { 22 }).await;
+    ///     scope.terminate("shut down").await
+    /// })
+    /// .await;
+    /// assert_eq!(result, "shut down");
+    /// assert_eq!(*flushed.lock().unwrap(), ["a", "b", "last"]);
+    /// # });
+    /// ```
+    ///
+    /// A job that does not finish within its grace period is dropped:
+    ///
+    /// ```rust
+    /// # use std::{sync::Arc, time::Duration};
+    /// # use moro::test_util::VirtualClock;
+    /// # futures::executor::block_on(async {
+    /// let clock = Arc::new(VirtualClock::new());
+    /// let result = moro::async_scope!(|scope| {
+    ///     scope.spawn_graceful(Duration::from_secs(5), |token| async move {
+    ///         token.cancelled().await;
+    ///         // Stuck shutting down.
+    ///         futures::future::pending::<()>().await
+    ///     });
+    ///     scope.terminate("shut down").await
+    /// })
+    /// .with_clock(clock.clone())
+    /// .await;
+    /// assert_eq!(result, "shut down");
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_graceful<T, F>(
+        &'scope self,
+        grace: Duration,
+        job: impl FnOnce(CancellationToken) -> F,
+    ) -> Spawned<impl Future<Output = Option<T>> + Send>
+    where
+        T: 'scope + Send,
+        F: Future<Output = T> + Send + 'scope,
+    {
+        let token = self.cancellation.token();
+        let future = job(token.clone());
+        let clock = self.clock();
+        let deadline = async move {
+            token.cancelled().await;
+            clock.sleep_until(clock.now() + grace).await;
+        };
+        let future = async move {
+            match futures::future::select(std::pin::pin!(future), std::pin::pin!(deadline)).await {
+                Either::Left((value, _)) => Some(value),
+                Either::Right(((), _)) => None,
+            }
+        };
+        let job = self.spawn_job(Location::caller(), None, None, true, future);
+        Spawned::for_job(job.rx.map(|r| r.ok().flatten()), job.abort, job.finished)
+    }
+
+    /// Records `value` as the scope's result unless it was already terminated.
+    fn set_terminated(&self, value: R) {
         let mut lock = self.terminated.lock().unwrap();
-        if lock.is_none() {
-            *lock = Some(value.into());
+        if lock.is_some() {
+            return;
+        }
+        *lock = Some(value);
+        drop(lock);
+        self.events.emit(|| ScopeEvent::Cancelled);
+        self.cancellation.cancel();
+
+        // Only graceful jobs keep running; the others are dropped at their
+        // next poll, which is right away unless a graceful job is running.
+        for (_, _, entry) in self.jobs.lock().unwrap().iter() {
+            if !entry.graceful {
+                entry.abort.abort();
+            }
         }
-        std::mem::drop(lock);
+    }
 
-        // The code below will never run
-        self.spawn(async { panic!() })
+    /// Terminates the scope with `value` (as [`terminate`][Self::terminate]
+    /// does) if any job currently in flight matches `predicate`. Returns
+    /// whether the scope was terminated.
+    ///
+    /// This lets a supervisor decide whether to abort based on the current
+    /// job population, e.g. "cancel if any job has been running for over
+    /// 30s". The predicate is invoked on a snapshot of every job in flight,
+    /// including the one calling `cancel_if`. Since termination takes effect
+    /// at the next await point, the caller stops once it next awaits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::{sync::Arc, time::Duration};
+    /// # use moro::test_util::VirtualClock;
+    /// # futures::executor::block_on(async {
+    /// let clock = Arc::new(VirtualClock::new());
+    /// let result = moro::async_scope!(|scope| {
+    ///     scope.spawn(futures::future::pending::<()>());
+    ///     let too_old = |job: &moro::JobInfo| job.age > Duration::from_secs(30);
+    ///
+    ///     assert!(!scope.cancel_if(too_old, "timed out"));
+    ///     clock.advance(Duration::from_secs(31));
+    ///     assert!(scope.cancel_if(too_old, "timed out"));
+    ///
+    ///     futures::future::pending().await
+    /// })
+    /// .with_clock(clock.clone())
+    /// .await;
+    /// assert_eq!(result, "timed out");
+    /// # });
+    /// ```
+    pub fn cancel_if(&self, predicate: impl Fn(&JobInfo) -> bool, value: R) -> bool {
+        let jobs = self.pending_jobs();
+        let matched = jobs.iter().any(predicate);
+        if matched {
+            self.set_terminated(value);
+        }
+        matched
     }
 
     /// Spawn a job that will run concurrently with everything else in the scope.
     /// The job may access stack fields defined outside the scope.
     /// The scope will not terminate until this job completes or the scope is cancelled.
+    ///
+    /// If the job panics, the panic unwinds out of the task polling the scope.
+    /// See [`spawn_catch_unwind`][Self::spawn_catch_unwind] to handle panics
+    /// instead. With the `tracing` feature, the job runs in a `moro::job` span
+    /// that records the location of the call to `spawn`, so a panic hook that
+    /// reports the current span points at the job that panicked.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let result = std::panic::catch_unwind(|| {
+    ///     futures::executor::block_on(moro::async_scope!(|scope| {
+    ///         scope.spawn(async { panic!("boom") });
+    ///     }))
+    /// });
+    /// assert!(result.is_err());
+    /// ```
+    #[track_caller]
     pub fn spawn<T>(
         &'scope self,
         future: impl Future<Output = T> + Send + 'scope,
@@ -143,18 +751,2426 @@ impl<'scope, 'env, R: Send> Scope<'scope, 'env, R> {
         // now is that caller will block which should (eventually) allow the
         // futures-unordered to be polled and make progress. Good enough.
 
-        let (tx, rx) = async_channel::bounded(1);
+        self.spawn_at(Location::caller(), future)
+    }
+
+    /// Like [`spawn`][Self::spawn], recording `location` as the place the job
+    /// was spawned. For methods that spawn jobs from closures, which do not
+    /// see the location of their caller.
+    pub(crate) fn spawn_at<T>(
+        &'scope self,
+        location: &'static Location<'static>,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send>
+    where
+        T: 'scope + Send,
+    {
+        self.spawn_labeled(location, None, future)
+    }
+
+    /// Spawn a job like [`spawn`][Self::spawn], labeled with `label`.
+    ///
+    /// The label shows up in [`pending_jobs`][Self::pending_jobs] and
+    /// [`dump_pending`][Self::dump_pending], which makes it possible to tell
+    /// which of many similar jobs is stuck when a scope does not complete.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    ///     let _done = scope.spawn_named("replica-0", async {});
+    ///     let stuck = scope.spawn_named(format!("replica-{}", 1), async { rx.await.unwrap() });
+    ///     // Let the jobs run.
+    ///     scope.spawn(async {}).await;
+    ///     let pending = scope.pending_jobs();
+    ///     assert_eq!(pending.len(), 1);
+    ///     assert_eq!(pending[0].label.as_deref(), Some("replica-1"));
+    ///     assert!(scope.dump_pending().contains("replica-1"));
+    ///     tx.send(()).unwrap();
+    ///     stuck.await;
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_named<T>(
+        &'scope self,
+        label: impl Into<Cow<'static, str>>,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send>
+    where
+        T: 'scope + Send,
+    {
+        self.spawn_labeled(Location::caller(), Some(label.into()), future)
+    }
+
+    fn spawn_labeled<T>(
+        &'scope self,
+        location: &'static Location<'static>,
+        label: Option<Cow<'static, str>>,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send>
+    where
+        T: 'scope + Send,
+    {
+        let job = self.spawn_job(location, None, label, false, future);
+
+        // The receiver is `Unpin`, and so is the resulting handle. The job
+        // only goes away without a result if the scope is terminated, in
+        // which case only graceful jobs would still be waiting for it.
+        Spawned::for_job(
+            job.rx.then(|r| match r {
+                Ok(v) => Either::Left(futures::future::ready(v)),
+                Err(oneshot::Canceled) => Either::Right(futures::future::pending()),
+            }),
+            job.abort,
+            job.finished,
+        )
+    }
+
+    /// Registers `future` as a job with the given `priority`, spawned at
+    /// `location`, and returns a handle to receive its result or abort it.
+    /// See [`Self::spawn_graceful`] for `graceful`.
+    fn spawn_job<T>(
+        &'scope self,
+        location: &'static Location<'static>,
+        priority: Option<u32>,
+        label: Option<Cow<'static, str>>,
+        graceful: bool,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> JobHandle<T>
+    where
+        T: 'scope + Send,
+    {
+        let id = self.next_job_id.fetch_add(1, Ordering::Relaxed);
+        let (abort, registration) = AbortHandle::new_pair();
+        let spawned_at = self.clock.lock().unwrap().now();
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(
+            future,
+            tracing::trace_span!("moro::job", id, label = label.as_deref(), %location),
+        );
+        let slot = self.jobs.lock().unwrap().insert(
+            id,
+            JobEntry {
+                priority,
+                abort: abort.clone(),
+                spawned_at,
+                location,
+                label,
+                graceful,
+            },
+        );
+        if graceful {
+            self.graceful.fetch_add(1, Ordering::Release);
+        } else if self.is_terminated() {
+            // Spawned by a job shutting down gracefully.
+            abort.abort();
+        }
+        let mut guard = JobGuard {
+            jobs: &self.jobs,
+            events: &self.events,
+            id,
+            slot,
+            graceful: graceful.then_some(&self.graceful),
+            completed: false,
+        };
+        self.events.emit(|| ScopeEvent::Spawned { id });
+
+        let (tx, rx) = oneshot::channel();
+        let finished = Arc::new(AtomicBool::new(false));
 
+        let job_finished = finished.clone();
         self.enqueued.lock().unwrap().push(Box::pin(async move {
-            let v = future.await;
-            let _ = tx.send(v).await;
+            if let Ok(v) = Abortable::new(future, registration).await {
+                guard.completed = true;
+                let _ = tx.send(v);
+                job_finished.store(true, Ordering::Release);
+            }
+            drop(guard);
         }));
 
-        Spawned::new(async move {
-            match rx.recv().await {
-                Ok(v) => v,
-                Err(e) => panic!("unexpected error: {e:?}"),
+        JobHandle {
+            rx,
+            abort,
+            finished,
+        }
+    }
+
+    /// Spawn a job with the given `priority` that may be *preempted* (cancelled)
+    /// to make room for higher-priority jobs.
+    ///
+    /// If the scope has a budget (see [`ScopeBody::with_budget`][crate::ScopeBody::with_budget])
+    /// and the number of prioritized jobs in flight has reached it, spawning
+    /// does not wait for room. Instead, the in-flight job with the lowest
+    /// priority is cancelled, provided its priority is strictly lower than
+    /// `priority`; among several such jobs, the most recently spawned one is
+    /// cancelled. If no job has a lower priority, the new job is the one that
+    /// is cancelled, without ever running. A cancelled job is dropped at its
+    /// next await point and its handle resolves to `None`.
+    ///
+    /// Jobs spawned with [`spawn`][Self::spawn] do not count against the
+    /// budget and are never preempted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use futures::future::pending;
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let low = scope.spawn_prioritized(1, pending::<u32>());
+    ///     let medium = scope.spawn_prioritized(2, pending::<u32>());
+    ///
+    ///     // The budget is exhausted, so `low` is cancelled to make room.
+    ///     let high = scope.spawn_prioritized(3, async { 22 });
+    ///     assert_eq!(low.await, None);
+    ///     assert_eq!(high.await, Some(22));
+    ///
+    ///     // `medium` and `other` fill the budget and outrank this job.
+    ///     let other = scope.spawn_prioritized(2, pending::<u32>());
+    ///     let lowest = scope.spawn_prioritized(0, async { 44 });
+    ///     assert_eq!(lowest.await, None);
+    ///
+    ///     drop((medium, other));
+    ///     scope.terminate(()).await
+    /// })
+    /// .with_budget(2)
+    /// .await;
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_prioritized<T>(
+        &'scope self,
+        priority: u32,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = Option<T>> + Send>
+    where
+        T: 'scope + Send,
+    {
+        let admitted = match *self.budget.lock().unwrap() {
+            None => true,
+            Some(budget) => {
+                let mut jobs = self.jobs.lock().unwrap();
+                let mut in_flight = 0;
+                // Lowest priority first; among equals, most recently spawned first.
+                let mut lowest: Option<(u32, Reverse<u64>, usize)> = None;
+                for (slot, id, entry) in jobs.iter() {
+                    if let Some(p) = entry.priority {
+                        in_flight += 1;
+                        let key = (p, Reverse(id), slot);
+                        if lowest.is_none_or(|l| key < l) {
+                            lowest = Some(key);
+                        }
+                    }
+                }
+                if in_flight < budget {
+                    true
+                } else {
+                    match lowest {
+                        Some((p, Reverse(id), slot)) if p < priority => {
+                            jobs.remove(slot, id).unwrap().abort.abort();
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+            }
+        };
+
+        if admitted {
+            let job = self.spawn_job(Location::caller(), Some(priority), None, false, future);
+            Spawned::for_job(job.rx.map(Result::ok), job.abort, job.finished)
+        } else {
+            Spawned::rejected(oneshot::channel().1.map(Result::ok))
+        }
+    }
+
+    /// Like [`spawn`][Self::spawn], but the job's output is paired with the time
+    /// it took, measured from the first poll of the job until it completed.
+    ///
+    /// Time is measured with the scope's [`Clock`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::{sync::Arc, time::Duration};
+    /// # use moro::test_util::VirtualClock;
+    /// # futures::executor::block_on(async {
+    /// // A clock whose time only moves when told to, see `moro::Clock`.
+    /// let clock = Arc::new(VirtualClock::new());
+    /// let timed = moro::async_scope!(|scope| {
+    ///     scope
+    ///         .spawn_timed(async {
+    ///             clock.advance(Duration::from_secs(5));
+    ///             22
+    ///         })
+    ///         .await
+    /// })
+    /// .with_clock(clock.clone())
+    /// .await;
+    /// assert_eq!(timed.value, 22);
+    /// assert_eq!(timed.elapsed, Duration::from_secs(5));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_timed<T>(
+        &'scope self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = Timed<T>> + Send>
+    where
+        T: 'scope + Send,
+    {
+        let clock = self.clock();
+        self.spawn(async move {
+            let start = clock.now();
+            let value = future.await;
+            Timed {
+                value,
+                elapsed: clock.now().saturating_duration_since(start),
             }
         })
     }
+
+    /// Spawns each of the validation `jobs` and waits for all of them,
+    /// returning the errors they produced with duplicates removed.
+    ///
+    /// Errors are reported in the order of the jobs that produced them, and an
+    /// error equal to one already reported is dropped. Unlike
+    /// [`or_cancel`][crate::Spawned::or_cancel], an error does not cancel the
+    /// scope: every job runs to completion.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let inputs = [1, -1, 2, -1, -3, -1];
+    /// let errors = moro::async_scope!(|scope| {
+    ///     scope
+    ///         .validate_all_dedup(inputs.iter().map(|&i| async move {
+    ///             if i < 0 {
+    ///                 Err(format!("negative input: {i}"))
+    ///             } else {
+    ///                 Ok(())
+    ///             }
+    ///         }))
+    ///         .await
+    /// })
+    /// .await;
+    /// assert_eq!(errors, ["negative input: -1", "negative input: -3"]);
+    /// # });
+    /// ```
+    ///
+    /// Duplicates are found by hashing, so many jobs failing in the same way
+    /// are cheap to deduplicate:
+    ///
+    /// ```rust
+    /// #[derive(Debug, PartialEq, Eq, Hash)]
+    /// enum Invalid {
+    ///     Odd,
+    ///     MultipleOfTen,
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let errors = moro::async_scope!(|scope| {
+    ///     scope
+    ///         .validate_all_dedup((1..=10_000).map(|i| async move {
+    ///             match i {
+    ///                 _ if i % 2 == 1 => Err(Invalid::Odd),
+    ///                 _ if i % 10 == 0 => Err(Invalid::MultipleOfTen),
+    ///                 _ => Ok(()),
+    ///             }
+    ///         }))
+    ///         .await
+    /// })
+    /// .await;
+    /// assert_eq!(errors, [Invalid::Odd, Invalid::MultipleOfTen]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn validate_all_dedup<E, J>(
+        &'scope self,
+        jobs: impl IntoIterator<Item = J>,
+    ) -> impl Future<Output = Vec<E>> + 'scope
+    where
+        E: Eq + Hash + Send + 'scope,
+        J: Future<Output = Result<(), E>> + Send + 'scope,
+    {
+        let location = Location::caller();
+        let handles: Vec<_> = jobs
+            .into_iter()
+            .map(|job| self.spawn_at(location, job))
+            .collect();
+        async move {
+            let mut errors = vec![];
+            for handle in handles {
+                if let Err(e) = handle.await {
+                    errors.push(e);
+                }
+            }
+
+            // Keep the first of each group of equal errors.
+            let mut seen = HashSet::new();
+            let first: Vec<bool> = errors.iter().map(|e| seen.insert(e)).collect();
+            let mut first = first.into_iter();
+            errors.retain(|_| first.next().unwrap());
+            errors
+        }
+    }
+
+    /// Spawns each of `jobs` and waits until they have all completed or
+    /// `deadline` resolves, whichever comes first.
+    ///
+    /// Returns the result of each job, in the order of `jobs`, with `None` for
+    /// the jobs that had not finished by the deadline, together with the
+    /// number of jobs that did finish. Unfinished jobs are cancelled at the
+    /// deadline. This is a best-effort "give me whatever's done" API for
+    /// latency-bounded aggregation, e.g. scatter-gather queries.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let (deadline_tx, deadline_rx) = futures::channel::oneshot::channel::<()>();
+    /// let (results, finished) = moro::async_scope!(|scope| {
+    ///     let replicas = (0..4).map(|i| async move {
+    ///         if i % 2 == 1 {
+    ///             // Odd replicas are too slow to answer.
+    ///             futures::future::pending::<()>().await;
+    ///         }
+    ///         i * 10
+    ///     });
+    ///     scope.spawn(async { drop(deadline_tx) });
+    ///     scope
+    ///         .spawn_all_timed(replicas, async { drop(deadline_rx.await) })
+    ///         .await
+    /// })
+    /// .await;
+    /// assert_eq!(results, [Some(0), None, Some(20), None]);
+    /// assert_eq!(finished, 2);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_all_timed<T, J>(
+        &'scope self,
+        jobs: impl IntoIterator<Item = J>,
+        deadline: impl Future<Output = ()> + Send + 'scope,
+    ) -> impl Future<Output = (Vec<Option<T>>, usize)> + Send + 'scope
+    where
+        T: Send + 'scope,
+        J: Future<Output = T> + Send + 'scope,
+    {
+        let location = Location::caller();
+        let mut aborts = vec![];
+        let mut running: FuturesUnordered<_> = jobs
+            .into_iter()
+            .enumerate()
+            .map(|(i, job)| {
+                let (job, abort) = futures::future::abortable(job);
+                aborts.push(abort);
+                self.spawn_at(location, job)
+                    .map(move |result| (i, result.ok()))
+            })
+            .collect();
+        let mut results: Vec<Option<T>> = aborts.iter().map(|_| None).collect();
+        async move {
+            let mut deadline = std::pin::pin!(deadline);
+            let mut finished = 0;
+            while let Either::Left((Some((i, result)), _)) =
+                futures::future::select(StreamExt::next(&mut running), deadline.as_mut()).await
+            {
+                finished += result.is_some() as usize;
+                results[i] = result;
+            }
+            for abort in aborts {
+                abort.abort();
+            }
+            (results, finished)
+        }
+    }
+
+    /// Spawns each of `jobs` and collects their successes, giving up at the
+    /// first error.
+    ///
+    /// If every job succeeds, the result is `Ok` with all the successes. As
+    /// soon as a job fails, the remaining jobs are canceled and the result is
+    /// `Err` with the successes gathered so far, alongside the error; unlike
+    /// [`join_all_results`][crate::join_all_results] with `?`, the partial
+    /// progress is not thrown away. Successes are in completion order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::test_util::yield_now;
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     // Each job yields `delay` times before finishing; `None` never finishes.
+    ///     let jobs = [(0, Some(Ok(1))), (2, Some(Ok(2))), (5, Some(Err("boom"))), (9, None)];
+    ///     let jobs = jobs.map(|(delay, result)| async move {
+    ///         for _ in 0..delay {
+    ///             yield_now().await;
+    ///         }
+    ///         match result {
+    ///             Some(result) => result,
+    ///             None => std::future::pending().await,
+    ///         }
+    ///     });
+    ///     scope.spawn_collect_or_cancel(jobs).await
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err((vec![1, 2], "boom")));
+    /// # });
+    /// ```
+    ///
+    /// Every job that succeeded before the error counts, even one whose
+    /// success is only noticed after the error:
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     let jobs = [Err("boom"), Ok(1)].map(|result| async move { result });
+    ///     scope.spawn_collect_or_cancel(jobs).await
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err((vec![1], "boom")));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_collect_or_cancel<O, E, J>(
+        &'scope self,
+        jobs: impl IntoIterator<Item = J>,
+    ) -> impl Future<Output = Result<Vec<O>, (Vec<O>, E)>> + Send + 'scope
+    where
+        O: Send + 'scope,
+        E: Send + 'scope,
+        J: Future<Output = Result<O, E>> + Send + 'scope,
+    {
+        // Jobs record their successes themselves, so that none is lost if
+        // the error is noticed before a success that completed earlier.
+        let successes = Arc::new(Mutex::new(vec![]));
+        let location = Location::caller();
+        let mut aborts = vec![];
+        let mut running: FuturesUnordered<_> = jobs
+            .into_iter()
+            .map(|job| {
+                let successes = successes.clone();
+                let (job, abort) = futures::future::abortable(async move {
+                    let value = job.await?;
+                    successes.lock().unwrap().push(value);
+                    Ok(())
+                });
+                aborts.push(abort);
+                self.spawn_at(location, job)
+            })
+            .collect();
+        async move {
+            while let Some(result) = StreamExt::next(&mut running).await {
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(error)) => {
+                        for abort in aborts {
+                            abort.abort();
+                        }
+                        let successes = std::mem::take(&mut *successes.lock().unwrap());
+                        return Err((successes, error));
+                    }
+                    Err(futures::future::Aborted) => unreachable!("aborted before an error"),
+                }
+            }
+            let successes = std::mem::take(&mut *successes.lock().unwrap());
+            Ok(successes)
+        }
+    }
+
+    /// Splits `inputs` into batches of `batch_size` and spawns one job per
+    /// batch, running `f` on it; returns the outputs of all batches, in the
+    /// order of `inputs`.
+    ///
+    /// Every job carries some overhead in the scope, which dominates when the
+    /// work per item is tiny; batching spawns far fewer jobs, while batches
+    /// still run concurrently with each other. `f` should return one output
+    /// per input, in order. See the `batched` example for a comparison with
+    /// spawning a job per item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let squares = moro::async_scope!(|scope| {
+    ///     scope
+    ///         .spawn_batched(1..=10, 4, |batch| async move {
+    ///             batch.into_iter().map(|n| n * n).collect()
+    ///         })
+    ///         .await
+    /// })
+    /// .await;
+    /// assert_eq!(squares, [1, 4, 9, 16, 25, 36, 49, 64, 81, 100]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_batched<I, T, F>(
+        &'scope self,
+        inputs: impl IntoIterator<Item = I>,
+        batch_size: usize,
+        f: impl Fn(Vec<I>) -> F,
+    ) -> impl Future<Output = Vec<T>> + Send + 'scope
+    where
+        T: Send + 'scope,
+        F: Future<Output = Vec<T>> + Send + 'scope,
+    {
+        assert!(batch_size > 0, "batch size must be positive");
+        let mut inputs = inputs.into_iter();
+        let mut batches = vec![];
+        loop {
+            let batch: Vec<I> = inputs.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                break;
+            }
+            batches.push(self.spawn(f(batch)));
+        }
+        async move {
+            let outputs = futures::future::join_all(batches).await;
+            outputs.into_iter().flatten().collect()
+        }
+    }
+
+    /// Spawn a *detached* job onto the tokio runtime. The job is **not** part
+    /// of the scope: the scope does not wait for it to complete, and
+    /// terminating the scope does not stop it.
+    ///
+    /// This deliberately opts out of structured concurrency. Because the job
+    /// may outlive the scope, it must be `'static` and so cannot borrow
+    /// anything from the scope or its environment. Prefer [`spawn`][Self::spawn]
+    /// unless the work truly must survive the scope, e.g. fire-and-forget
+    /// logging. Use the returned [`JoinHandle`](tokio::task::JoinHandle) to
+    /// wait for or abort the job.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let (tx, rx) = tokio::sync::oneshot::channel::<u32>();
+    /// let handle = moro::async_scope!(|scope| {
+    ///     // The scope does not wait for this job, which could not
+    ///     // complete before the scope is done anyway.
+    ///     scope.spawn_detached(async move {
+    ///         let v = rx.await.unwrap();
+    ///         assert_eq!(v, 22);
+    ///     })
+    /// })
+    /// .await;
+    /// tx.send(22).unwrap();
+    /// handle.await.unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn spawn_detached(
+        &self,
+        future: impl Future<Output = ()> + Send + 'static,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(future)
+    }
+
+    /// Spawn a job whose panics are caught: the returned [`Spawned`] yields
+    /// `Err` with the panic's payload instead of the panic unwinding through
+    /// the scope.
+    ///
+    /// With [`spawn`][Self::spawn], a panicking job unwinds out of the task
+    /// polling the scope, taking the scope body and every other job with it.
+    /// Here, the other jobs keep running, and the awaiter decides what to do,
+    /// e.g. [`terminate`][Self::terminate] the scope or carry on.
+    ///
+    /// The job's future is treated as unwind safe. This is only sound if
+    /// whatever the job shares with the rest of the scope remains valid after
+    /// a panic half-way through an update: a `Mutex` it held is poisoned, but
+    /// e.g. a `RefCell` or atomics may be left in an inconsistent state that
+    /// others then observe. See [`std::panic::UnwindSafe`]. Panics are still
+    /// reported by the panic hook, and nothing is caught if panics abort.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     let sibling = scope.spawn(async { 22 });
+    ///     let panicky = scope.spawn_catch_unwind(async {
+    ///         if true {
+    ///             panic!("boom");
+    ///         }
+    ///         0
+    ///     });
+    ///     let payload = panicky.await.unwrap_err();
+    ///     let message = *payload.downcast::<&str>().unwrap();
+    ///     (message, sibling.await)
+    /// })
+    /// .await;
+    /// assert_eq!(result, ("boom", 22));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_catch_unwind<T>(
+        &'scope self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = Result<T, Box<dyn Any + Send>>> + Send>
+    where
+        T: 'scope + Send,
+    {
+        self.spawn(AssertUnwindSafe(future).catch_unwind())
+    }
+
+    /// Spawn a job that runs the CPU-bound or blocking closure `f` on a
+    /// separate thread, so that it does not hold up the executor.
+    ///
+    /// When the `tokio` feature is enabled and this is called inside a tokio
+    /// runtime, `f` runs on tokio's blocking thread pool; otherwise it runs
+    /// on a freshly spawned thread. The job otherwise behaves like one
+    /// started with [`spawn`][Self::spawn]: the scope waits for it, and the
+    /// returned [`Spawned`] yields its result. If `f` panics, the panic is
+    /// propagated to whoever awaits the job.
+    ///
+    /// A running closure cannot be interrupted. Terminating the scope stops
+    /// waiting for it right away, but the thread keeps running until `f`
+    /// returns, and its result is discarded. For the same reason, `f` must be
+    /// `'static`: the thread may outlive the scope, so it cannot borrow
+    /// anything from the scope or its environment. Move (or clone, e.g. into
+    /// an [`Arc`]) the data it needs instead.
+    ///
+    /// ```rust,compile_fail,E0597
+    /// # futures::executor::block_on(async {
+    /// let data = vec![1, 2, 3];
+    /// moro::async_scope!(|scope| {
+    ///     // NOT ok: the closure borrows `data`.
+    ///     scope.spawn_blocking(|| data.iter().sum::<u32>()).await
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let data = vec![1u32, 2, 3, 4];
+    /// let result = moro::async_scope!(|scope| {
+    ///     let checksum = scope.spawn_blocking(move || {
+    ///         data.iter().fold(0u32, |sum, x| sum.wrapping_mul(31).wrapping_add(*x))
+    ///     });
+    ///     let other = scope.spawn(async { 1 });
+    ///     checksum.await + other.await
+    /// })
+    /// .await;
+    /// assert_eq!(result, 31810 + 1);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_blocking<T>(
+        &'scope self,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> Spawned<impl Future<Output = T> + Send>
+    where
+        T: Send + 'static,
+    {
+        let (run, result) = offload(f);
+
+        #[cfg(feature = "tokio")]
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(run)),
+            Err(_) => drop(std::thread::spawn(run)),
+        }
+        #[cfg(not(feature = "tokio"))]
+        drop(std::thread::spawn(run));
+
+        self.spawn(result)
+    }
+
+    /// Spawn a job that runs the CPU-bound closure `f` on rayon's global
+    /// thread pool, where it can use rayon's parallel iterators. Requires the
+    /// `rayon` feature.
+    ///
+    /// This behaves like [`spawn_blocking`][Self::spawn_blocking], except
+    /// for where `f` runs: the scope waits for the job, panics propagate to
+    /// whoever awaits it, and terminating the scope stops waiting for `f`
+    /// without interrupting it. Like there, `f` must be `'static`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rayon::prelude::*;
+    ///
+    /// # futures::executor::block_on(async {
+    /// let data: Vec<u64> = (1..=1000).collect();
+    /// let sum = moro::async_scope!(|scope| {
+    ///     scope.spawn_rayon(move || data.par_iter().sum::<u64>()).await
+    /// })
+    /// .await;
+    /// assert_eq!(sum, 500500);
+    /// # });
+    /// ```
+    ///
+    /// Terminating the scope does not wait for a running closure:
+    ///
+    /// ```rust
+    /// # use std::sync::mpsc;
+    /// # futures::executor::block_on(async {
+    /// let (release, released) = mpsc::channel::<()>();
+    /// let (started, has_started) = futures::channel::oneshot::channel();
+    /// let result = moro::async_scope!(|scope| {
+    ///     scope.spawn_rayon(move || {
+    ///         started.send(()).unwrap();
+    ///         released.recv().unwrap();
+    ///     });
+    ///     has_started.await.unwrap();
+    ///     scope.terminate("cancelled").await
+    /// })
+    /// .await;
+    /// assert_eq!(result, "cancelled");
+    ///
+    /// // The closure is still running, until we let it finish.
+    /// release.send(()).unwrap();
+    /// # });
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[track_caller]
+    pub fn spawn_rayon<T>(
+        &'scope self,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> Spawned<impl Future<Output = T> + Send>
+    where
+        T: Send + 'static,
+    {
+        let (run, result) = offload(f);
+        rayon::spawn(run);
+        self.spawn(result)
+    }
+
+    /// Spawn a job that can contribute any number of values to the scope's
+    /// [`outputs`][Self::outputs] while it runs, in addition to its result.
+    ///
+    /// `job` is invoked right away with a [`Yielder`]; each call to
+    /// [`yield_`][Yielder::yield_] pushes a value that the scope body (or
+    /// another job) receives from [`outputs`][Self::outputs]. If the consumer
+    /// lags behind, `yield_` waits until it catches up, so a job that yields
+    /// values nobody consumes will never finish.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     for word in ["hello", "world"] {
+    ///         scope.spawn_yielding(move |yielder| async move {
+    ///             for c in word.chars() {
+    ///                 yielder.yield_(c).await;
+    ///             }
+    ///         });
+    ///     }
+    ///
+    ///     let mut outputs = scope.outputs::<char>();
+    ///     let mut chars = vec![];
+    ///     while let Some(c) = outputs.next().await {
+    ///         chars.push(c);
+    ///     }
+    ///     chars.sort();
+    ///     chars.into_iter().collect::<String>()
+    /// })
+    /// .await;
+    /// assert_eq!(result, "dehllloorw");
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_yielding<T, O, F>(
+        &'scope self,
+        job: impl FnOnce(Yielder<'scope, T>) -> F,
+    ) -> Spawned<impl Future<Output = O> + Send>
+    where
+        T: Send + 'static,
+        O: Send + 'scope,
+        F: Future<Output = O> + Send + 'scope,
+    {
+        self.spawn(job(outputs::yielder(&self.outputs)))
+    }
+
+    /// Spawn a job whose result is streamed by
+    /// [`ScopeBody::results`][crate::ScopeBody::results], in completion order
+    /// with the results of the scope's other such jobs.
+    ///
+    /// The result is delivered like a value yielded with
+    /// [`spawn_yielding`][Self::spawn_yielding], so it can also be received
+    /// from [`outputs`][Self::outputs]. Like there, the job only completes
+    /// once its result has been taken.
+    #[track_caller]
+    pub fn spawn_streamed<T>(
+        &'scope self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = ()> + Send + 'scope>
+    where
+        T: Send + 'static,
+    {
+        let yielder = outputs::yielder(&self.outputs);
+        self.spawn(async move { yielder.yield_(future.await).await })
+    }
+
+    /// Returns the values of type `T` yielded by jobs spawned with
+    /// [`spawn_yielding`][Self::spawn_yielding], in the order they are yielded.
+    ///
+    /// Values are kept until they are taken, so `outputs` may be called
+    /// before or after the jobs yield them. The returned iterator ends once
+    /// the scope has no jobs left, so it should be consumed by the body
+    /// rather than by a job. Jobs spawned with `spawn_yielding` after that
+    /// point yield into a new channel, visible through a fresh call to
+    /// `outputs`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     scope.spawn_yielding(|yielder| async move { yielder.yield_(22u32).await });
+    ///     // Let the job run to completion before looking at its outputs.
+    ///     scope.spawn(async {}).await;
+    ///     let mut outputs = scope.outputs::<u32>();
+    ///     let first = outputs.next().await;
+    ///     // The iterator ends since no job is left.
+    ///     (first, outputs.next().await, scope.outputs::<String>().next().await)
+    /// })
+    /// .await;
+    /// assert_eq!(result, (Some(22), None, None));
+    /// # });
+    /// ```
+    pub fn outputs<T: Send + 'static>(&self) -> Outputs<T> {
+        outputs::outputs(&self.outputs)
+    }
+
+    /// Spawn a job that is expected to be the `position`-th (counting from zero)
+    /// of the jobs spawned with this method to complete.
+    ///
+    /// Jobs in a scope may complete in any order. This is a testing aid for
+    /// deterministic simulations: in debug builds, the job panics upon
+    /// completion if it completes out of the declared order, which surfaces
+    /// unexpected nondeterminism early. In release builds it is identical to
+    /// [`spawn`][Self::spawn].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,should_panic
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let slow = scope.spawn_ordered_debug(0, async {
+    ///         futures::pending!();
+    ///     });
+    ///     // Panics: this job completes before the one declared to be first.
+    ///     let fast = scope.spawn_ordered_debug(1, async {});
+    ///     fast.await;
+    ///     slow.await;
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_ordered_debug<T>(
+        &'scope self,
+        position: usize,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send + 'scope>
+    where
+        T: 'scope + Send,
+    {
+        self.spawn(async move {
+            let value = future.await;
+            if cfg!(debug_assertions) {
+                let expected = self.next_ordered.fetch_add(1, Ordering::Relaxed);
+                assert_eq!(
+                    position, expected,
+                    "job completed out of order: declared position {position}, \
+                     but {expected} jobs completed before it",
+                );
+            }
+            value
+        })
+    }
+
+    /// Like [`spawn`][Self::spawn], but in debug builds, panics if the future
+    /// occupies more than `MAX` bytes.
+    ///
+    /// Every job is stored in the scope, so spawning very large futures (for
+    /// example, ones holding big arrays across await points) bloats memory.
+    /// This catches such futures early, nudging you to box or shrink them. In
+    /// release builds the check compiles away.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,should_panic
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let small = scope.spawn_size_checked::<1024, _>(async { 22 });
+    ///
+    ///     // Panics: the buffer lives across an await point,
+    ///     // so it is part of the future.
+    ///     let big = scope.spawn_size_checked::<1024, _>(async {
+    ///         let buffer = [0u8; 4096];
+    ///         futures::future::ready(()).await;
+    ///         buffer.len()
+    ///     });
+    ///     small.await + big.await
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_size_checked<const MAX: usize, T>(
+        &'scope self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send>
+    where
+        T: 'scope + Send,
+    {
+        debug_assert!(
+            std::mem::size_of_val(&future) <= MAX,
+            "spawned future is {} bytes, more than the maximum of {MAX}; consider boxing it",
+            std::mem::size_of_val(&future),
+        );
+        self.spawn(future)
+    }
+
+    /// Spawn a job that can be recovered if it does not finish in time.
+    ///
+    /// The job's future is created by calling `make`. If the scope is
+    /// interrupted by [`ScopeBody::drain_or_recover`][crate::ScopeBody::drain_or_recover]
+    /// before the job completes, the job is returned as a [`PendingJob`]
+    /// from which it can be started again, since `make` can create a fresh
+    /// future. Because a pending job outlives the scope, `make` (and the
+    /// futures it creates) can only borrow from outside the scope.
+    #[track_caller]
+    pub fn spawn_restartable<F>(
+        &'scope self,
+        make: impl Fn() -> F + Send + Sync + 'env,
+    ) -> Spawned<impl Future<Output = ()> + Send + 'scope>
+    where
+        F: Future<Output = ()> + Send + 'env,
+    {
+        let job = PendingJob::new(Arc::new(move || Box::pin(make()) as BoxFuture<'scope, ()>));
+        let future = job.start();
+        let key = self.restartable.lock().unwrap().insert(job);
+        self.spawn(async move {
+            future.await;
+            self.restartable.lock().unwrap().remove(key);
+        })
+    }
+
+    /// Returns a stream of the scope's lifecycle [events](ScopeEvent) from
+    /// now on: jobs being spawned, completing, or being dropped early, and
+    /// the scope being terminated.
+    ///
+    /// The stream ends when the scope is dropped. Events are buffered without
+    /// bound until consumed, so a subscriber that stops consuming should drop
+    /// the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use futures::StreamExt;
+    /// # use moro::ScopeEvent;
+    /// # futures::executor::block_on(async {
+    /// let events = moro::async_scope!(|scope| {
+    ///     let events = scope.events();
+    ///     let quick = scope.spawn(async {});
+    ///     scope.spawn(futures::future::pending::<()>());
+    ///     quick.await;
+    ///     scope.terminate(events).await
+    /// })
+    /// .await;
+    ///
+    /// assert_eq!(
+    ///     events.collect::<Vec<_>>().await,
+    ///     [
+    ///         ScopeEvent::Spawned { id: 0 },
+    ///         ScopeEvent::Spawned { id: 1 },
+    ///         ScopeEvent::Completed { id: 0 },
+    ///         ScopeEvent::Cancelled,
+    ///         ScopeEvent::Terminated { id: 1 },
+    ///     ]
+    /// );
+    /// # });
+    /// ```
+    #[cfg(feature = "events")]
+    pub fn events(&self) -> crate::ScopeEvents {
+        self.events.subscribe()
+    }
+
+    /// Creates an empty [`JobSet`], into which jobs can be spawned whose
+    /// results are then yielded in completion order.
+    pub fn job_set<T>(&'scope self) -> JobSet<'scope, 'env, R, T>
+    where
+        T: Send + 'scope,
+    {
+        JobSet::new(self)
+    }
+
+    /// Spawns each of `jobs` and returns a [`JobSet`] yielding their results
+    /// as they complete.
+    ///
+    /// Since `JobSet` is an [`AsyncIterator`][crate::AsyncIterator], the
+    /// results can be processed with the iterator combinators. Unlike
+    /// draining a `FuturesUnordered`, the jobs run in the scope whether or
+    /// not the results are being consumed, so a slow consumer cannot stall
+    /// them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let result = moro::scope(async |scope| {
+    ///     let mut evens = scope
+    ///         .results((0..6).map(|i| async move { i * 3 }))
+    ///         .filter(async |i| i % 2 == 0);
+    ///     let mut result = vec![];
+    ///     while let Some(i) = evens.next().await {
+    ///         result.push(i);
+    ///     }
+    ///     result.sort();
+    ///     result
+    /// })
+    /// .await;
+    /// assert_eq!(result, [0, 6, 12]);
+    /// # });
+    /// ```
+    ///
+    /// Results arrive in the order the jobs complete:
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # use moro::test_util::yield_now;
+    /// # futures::executor::block_on(async {
+    /// let order = moro::scope(async |scope| {
+    ///     let mut done = scope.results((0..3).map(|i| async move {
+    ///         // Later jobs finish sooner.
+    ///         for _ in i..3 {
+    ///             yield_now().await;
+    ///         }
+    ///         i
+    ///     }));
+    ///     let mut order = vec![];
+    ///     while let Some(i) = done.next().await {
+    ///         order.push(i);
+    ///     }
+    ///     order
+    /// })
+    /// .await;
+    /// assert_eq!(order, [2, 1, 0]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn results<T, J>(
+        &'scope self,
+        jobs: impl IntoIterator<Item = J>,
+    ) -> JobSet<'scope, 'env, R, T>
+    where
+        T: Send + 'scope,
+        J: Future<Output = T> + Send + 'scope,
+    {
+        self.results_at(Location::caller(), jobs)
+    }
+
+    /// Like [`results`][Self::results], recording `location` as the place
+    /// the jobs were spawned; see [`spawn_at`][Self::spawn_at].
+    fn results_at<T, J>(
+        &'scope self,
+        location: &'static Location<'static>,
+        jobs: impl IntoIterator<Item = J>,
+    ) -> JobSet<'scope, 'env, R, T>
+    where
+        T: Send + 'scope,
+        J: Future<Output = T> + Send + 'scope,
+    {
+        let mut set = self.job_set();
+        for job in jobs {
+            set.spawn_at(location, job);
+        }
+        set
+    }
+
+    /// Spawns each of `jobs` and extends `target` with each job's result as
+    /// it completes.
+    ///
+    /// The caller owns `target`, so it can be pre-sized or be any collection
+    /// implementing [`Extend`], e.g. a `HashMap` filled by jobs returning
+    /// key-value pairs, and no intermediate `Vec` is built. If the scope is
+    /// terminated while this runs, `target` keeps the results added so far.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # futures::executor::block_on(async {
+    /// let mut lengths = HashMap::with_capacity(3);
+    /// moro::scope(async |scope| {
+    ///     let jobs = ["a", "bb", "ccc"].map(|word| async move { (word, word.len()) });
+    ///     scope.collect_into_extend(&mut lengths, jobs).await;
+    /// })
+    /// .await;
+    /// assert_eq!(lengths, HashMap::from([("a", 1), ("bb", 2), ("ccc", 3)]));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn collect_into_extend<'t, C, T, J, I>(
+        &'scope self,
+        target: &'t mut C,
+        jobs: I,
+    ) -> impl Future<Output = ()> + use<'scope, 'env, 't, R, C, T, J, I>
+    where
+        C: Extend<T> + Send,
+        T: Send + 'scope,
+        J: Future<Output = T> + Send + 'scope,
+        I: IntoIterator<Item = J>,
+    {
+        // The jobs are spawned once the future is first polled.
+        let location = Location::caller();
+        async move {
+            let mut results = self.results_at(location, jobs);
+            while let Some(result) = results.next().await {
+                target.extend(std::iter::once(result));
+            }
+        }
+    }
+
+    /// Spawns each of `jobs` and sends each job's result into `tx` as it
+    /// completes, e.g. to feed the results into a pipeline the caller already
+    /// built around the receiving end.
+    ///
+    /// Each send waits until the channel has room, so a slow consumer slows
+    /// down the forwarding (the jobs themselves keep running). `tx` is
+    /// dropped once all results are forwarded, which closes the channel if it
+    /// has no other senders. If the receiver is dropped first, this stops
+    /// forwarding and returns the error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use futures::{channel::mpsc, StreamExt};
+    /// # use moro::test_util::yield_now;
+    /// # futures::executor::block_on(async {
+    /// let done = &AtomicBool::new(false);
+    /// let mut received = moro::scope(async |scope| {
+    ///     // Holds a single message.
+    ///     let (tx, mut rx) = mpsc::channel(0);
+    ///     scope.spawn(async move {
+    ///         let jobs = (1..=5).map(|i| async move { i * 10 });
+    ///         scope.forward_to(tx, jobs).await.unwrap();
+    ///         done.store(true, Ordering::SeqCst);
+    ///     });
+    ///
+    ///     let mut received = vec![];
+    ///     while let Some(value) = rx.next().await {
+    ///         received.push(value);
+    ///         for _ in 0..10 {
+    ///             yield_now().await;
+    ///         }
+    ///         // Forwarding waits for this slow consumer.
+    ///         assert!(!done.load(Ordering::SeqCst) || received.len() >= 4);
+    ///     }
+    ///     received
+    /// })
+    /// .await;
+    /// assert!(done.load(Ordering::SeqCst));
+    /// received.sort();
+    /// assert_eq!(received, [10, 20, 30, 40, 50]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn forward_to<T, J, I>(
+        &'scope self,
+        mut tx: mpsc::Sender<T>,
+        jobs: I,
+    ) -> impl Future<Output = Result<(), mpsc::SendError>> + use<'scope, 'env, R, T, J, I>
+    where
+        T: Send + 'scope,
+        J: Future<Output = T> + Send + 'scope,
+        I: IntoIterator<Item = J>,
+    {
+        // The jobs are spawned once the future is first polled.
+        let location = Location::caller();
+        async move {
+            let mut results = self.results_at(location, jobs);
+            while let Some(result) = results.next().await {
+                tx.send(result).await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Ties `stream` to the scope, returning an iterator over its items.
+    ///
+    /// The stream is polled by a job of the scope, so if the scope is
+    /// terminated, the stream is dropped right away at whatever point it is
+    /// pending, e.g. closing the network connection it is reading from. It is
+    /// also dropped once the returned iterator is dropped. The job reads one
+    /// item ahead of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use futures::StreamExt;
+    /// # use moro::AsyncIterator;
+    /// struct Connection<'a>(&'a AtomicBool);
+    ///
+    /// impl Drop for Connection<'_> {
+    ///     fn drop(&mut self) {
+    ///         self.0.store(true, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let closed = AtomicBool::new(false);
+    /// let result = moro::scope(async |scope| {
+    ///     let connection = Connection(&closed);
+    ///     // Yields two messages, then waits forever for more.
+    ///     let messages = futures::stream::iter([1, 2])
+    ///         .chain(futures::stream::pending())
+    ///         .map(move |msg| {
+    ///             let _connection = &connection;
+    ///             msg
+    ///         });
+    ///     let mut messages = scope.stream_within_scope(messages);
+    ///     while let Some(msg) = messages.next().await {
+    ///         if msg == 2 {
+    ///             scope.terminate(Err("enough")).await
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err("enough"));
+    /// assert!(closed.load(Ordering::SeqCst));
+    /// # });
+    /// ```
+    ///
+    /// Dropping the iterator drops the stream even while the scope goes on:
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use futures::StreamExt;
+    /// # use moro::AsyncIterator;
+    /// # use moro::test_util::yield_now;
+    /// # struct Connection<'a>(&'a AtomicBool);
+    /// # impl Drop for Connection<'_> {
+    /// #     fn drop(&mut self) {
+    /// #         self.0.store(true, Ordering::SeqCst);
+    /// #     }
+    /// # }
+    /// # futures::executor::block_on(async {
+    /// let closed = AtomicBool::new(false);
+    /// moro::scope(async |scope| {
+    ///     let connection = Connection(&closed);
+    ///     let messages = futures::stream::iter([1])
+    ///         .chain(futures::stream::pending())
+    ///         .map(move |msg| {
+    ///             let _connection = &connection;
+    ///             msg
+    ///         });
+    ///     let mut messages = scope.stream_within_scope(messages);
+    ///     assert_eq!(messages.next().await, Some(1));
+    ///     drop(messages);
+    ///     yield_now().await;
+    ///     assert!(closed.load(Ordering::SeqCst));
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    pub fn stream_within_scope<S>(
+        &'scope self,
+        stream: S,
+    ) -> impl AsyncIterator<Item = S::Item> + 'scope
+    where
+        S: futures::Stream + Send + 'scope,
+        S::Item: Send + 'scope,
+    {
+        let (tx, rx) = async_channel::bounded(1);
+        let job = self.spawn_cancel_on_drop(async move {
+            let mut stream = std::pin::pin!(stream);
+            while let Some(item) = StreamExt::next(&mut stream).await {
+                if tx.send(item).await.is_err() {
+                    break;
+                }
+            }
+        });
+        ScopedStream { rx, _job: job }
+    }
+
+    /// Releases memory the scope holds for jobs that are no longer running.
+    ///
+    /// The scope keeps track of its jobs in vectors that grow to accommodate
+    /// the largest number of jobs that were in flight at once, and keep that
+    /// capacity afterwards. For long-lived scopes that see occasional bursts
+    /// of jobs (e.g. a server's top-level scope), calling this once a burst
+    /// has drained returns that capacity to the allocator.
+    ///
+    /// What is reclaimed:
+    ///
+    /// - the slots that recorded completed jobs (see
+    ///   [`pending_jobs`][Self::pending_jobs]), past the last slot still in
+    ///   use; free slots before it are kept for later jobs;
+    /// - the queue of jobs spawned but not yet polled;
+    /// - in a [biased][crate::scope_fn_biased] scope, the list of running jobs,
+    ///   unless this is called from one of the scope's jobs, which run while
+    ///   that list is in use.
+    ///
+    /// The futures of completed jobs are freed as they complete, so they are
+    /// not affected. Memory held for specific kinds of jobs, such as
+    /// [`spawn_restartable`][Self::spawn_restartable] jobs, is not reclaimed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use moro::test_util::job_capacity;
+    ///
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let burst: Vec<_> = (0..1000).map(|i| scope.spawn(async move { i })).collect();
+    ///     for job in burst {
+    ///         job.await;
+    ///     }
+    ///     let before = job_capacity(scope);
+    ///     assert!(before >= 1000);
+    ///
+    ///     scope.shrink_to_fit();
+    ///     assert!(job_capacity(scope) < before / 10);
+    ///
+    ///     // The scope keeps working as before.
+    ///     assert_eq!(scope.spawn(async { 1 }).await, 1);
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    pub fn shrink_to_fit(&self) {
+        self.jobs.lock().unwrap().shrink_to_fit();
+        self.enqueued.lock().unwrap().shrink_to_fit();
+
+        // `ordered` is locked while jobs are being polled, so a job calling
+        // this cannot shrink it.
+        if let Ok(mut ordered) = self.ordered.try_lock() {
+            ordered.shrink_to_fit();
+        }
+    }
+
+    /// Number of jobs the scope can track without allocating; see
+    /// `test_util::job_capacity`.
+    #[cfg(feature = "test-util")]
+    pub(crate) fn job_capacity(&self) -> usize {
+        self.jobs.lock().unwrap().capacity()
+    }
+
+    /// Spawn a job copying all bytes from `reader` into `writer`, resolving
+    /// to the number of bytes copied.
+    ///
+    /// This packages the common proxy/pipe pattern as a scope job. Like any
+    /// job, the copy stops if the scope is terminated: `reader` and `writer`
+    /// are dropped at the pending read or write. The bytes `writer` accepted
+    /// so far stay written, but the copy reads ahead into a buffer, and bytes
+    /// read from `reader` that `writer` has not accepted yet are lost with it.
+    /// If those matter, e.g. to resume the copy later, copy with your own
+    /// buffer instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let mut out = vec![];
+    /// let copied = moro::async_scope!(|scope| {
+    ///     let reader = futures::io::Cursor::new(b"hello, world");
+    ///     scope.spawn_copy(reader, &mut out).await
+    /// })
+    /// .await
+    /// .unwrap();
+    /// assert_eq!(copied, 12);
+    /// assert_eq!(out, b"hello, world");
+    /// # });
+    /// ```
+    ///
+    /// Terminating the scope in the middle of a copy:
+    ///
+    /// ```rust
+    /// # use std::{io, pin::Pin, sync::atomic::{AtomicBool, Ordering}, task::{Context, Poll}};
+    /// # use futures::{channel::oneshot, io::{AsyncRead, AsyncReadExt, Cursor}};
+    /// /// A reader that never produces data, reporting when it is first polled
+    /// /// and when it is dropped.
+    /// struct Stalled<'a> {
+    ///     polled: Option<oneshot::Sender<()>>,
+    ///     dropped: &'a AtomicBool,
+    /// }
+    ///
+    /// impl AsyncRead for Stalled<'_> {
+    ///     fn poll_read(mut self: Pin<&mut Self>, _: &mut Context<'_>, _: &mut [u8]) -> Poll<io::Result<usize>> {
+    ///         if let Some(tx) = self.polled.take() {
+    ///             tx.send(()).unwrap();
+    ///         }
+    ///         Poll::Pending
+    ///     }
+    /// }
+    ///
+    /// impl Drop for Stalled<'_> {
+    ///     fn drop(&mut self) {
+    ///         self.dropped.store(true, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let dropped = AtomicBool::new(false);
+    /// let mut out = vec![];
+    /// let result = moro::async_scope!(|scope| {
+    ///     let (tx, rx) = oneshot::channel();
+    ///     let reader = Cursor::new(b"abc").chain(Stalled { polled: Some(tx), dropped: &dropped });
+    ///     let _copy = scope.spawn_copy(reader, &mut out);
+    ///     rx.await.unwrap();
+    ///     scope.terminate("cancelled").await
+    /// })
+    /// .await;
+    /// assert_eq!(result, "cancelled");
+    /// assert!(dropped.load(Ordering::SeqCst));
+    /// // Written before the reader stalled.
+    /// assert_eq!(out, b"abc");
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_copy<Rd, W>(
+        &'scope self,
+        reader: Rd,
+        mut writer: W,
+    ) -> Spawned<impl Future<Output = std::io::Result<u64>> + Send>
+    where
+        Rd: futures::io::AsyncRead + Send + 'scope,
+        W: futures::io::AsyncWrite + Unpin + Send + 'scope,
+    {
+        self.spawn(async move { futures::io::copy(reader, &mut writer).await })
+    }
+
+    /// Spawn a job, declaring explicitly which data from outside the scope
+    /// it borrows.
+    ///
+    /// Jobs may borrow data that outlives the scope, but not data owned by
+    /// the scope body, since jobs may outlive the body. With
+    /// [`spawn`][Self::spawn], getting this wrong results in lifetime errors
+    /// that point at the spawn. Here, `make` receives a [`Borrows`] token and
+    /// takes each borrow through it before creating the job's future, so
+    /// every borrow is visible in the code, and a borrow of data that does
+    /// not outlive the scope is reported at the offending variable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let names = vec!["a", "b"];
+    /// let mut log = vec![];
+    /// moro::async_scope!(|scope| {
+    ///     scope.spawn_borrowing(|env| {
+    ///         let names = env.share(&names);
+    ///         let log = env.exclusive(&mut log);
+    ///         async move { log.extend(names.iter().map(|n| n.len())) }
+    ///     });
+    /// })
+    /// .await;
+    /// assert_eq!(log, [1, 1]);
+    /// # });
+    /// ```
+    ///
+    /// Borrowing a variable of the scope body fails, with an error that
+    /// `local` does not live long enough pointing at `env.share(&local)`:
+    ///
+    /// ```compile_fail,E0597
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let local = 22;
+    ///     scope
+    ///         .spawn_borrowing(|env| {
+    ///             let local = env.share(&local);
+    ///             async move { *local }
+    ///         })
+    ///         .await
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_borrowing<T, F>(
+        &'scope self,
+        make: impl FnOnce(Borrows<'env>) -> F,
+    ) -> Spawned<impl Future<Output = T> + Send + 'scope>
+    where
+        F: Future<Output = T> + Send + 'scope,
+        T: 'scope + Send,
+    {
+        self.spawn(make(Borrows::new()))
+    }
+
+    /// Returns the scope's context value of type `C`, if one was set with
+    /// [`ScopeBody::with_context`][crate::ScopeBody::with_context] or
+    /// inherited with [`ScopeBody::inherit_context`][crate::ScopeBody::inherit_context].
+    ///
+    /// This gives every job access to request-scoped data, such as trace ids,
+    /// credentials or tenant information, without threading it through each
+    /// closure.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct TraceId(u64);
+    ///
+    /// # futures::executor::block_on(async {
+    /// let trace = moro::async_scope!(|scope| {
+    ///     scope
+    ///         .spawn(async {
+    ///             scope
+    ///                 .spawn(async {
+    ///                     // A nested scope sees the context if it inherits it.
+    ///                     moro::async_scope!(|inner| inner.context::<TraceId>())
+    ///                         .inherit_context(scope)
+    ///                         .await
+    ///                 })
+    ///                 .await
+    ///         })
+    ///         .await
+    /// })
+    /// .with_context(TraceId(22))
+    /// .await;
+    /// assert_eq!(trace, Some(TraceId(22)));
+    /// # });
+    /// ```
+    pub fn context<C: Clone + 'static>(&self) -> Option<C> {
+        self.contexts.get()
+    }
+
+    /// Returns a snapshot of the jobs in the scope that have not completed,
+    /// in spawn order.
+    ///
+    /// Each [`JobInfo`] records where the job was spawned, so this is useful
+    /// to diagnose a scope that does not complete.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let line = line!() + 1;
+    ///     let stuck = scope.spawn(futures::future::pending::<()>());
+    ///     let jobs = scope.pending_jobs();
+    ///     assert_eq!(jobs.len(), 1);
+    ///     assert_eq!(jobs[0].location.file(), file!());
+    ///     assert_eq!(jobs[0].location.line(), line);
+    ///     drop(stuck);
+    ///     scope.terminate(()).await
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    ///
+    /// Jobs spawned by methods taking several jobs at once are recorded at the
+    /// call of that method:
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let stuck = || futures::future::pending::<Result<(), ()>>();
+    ///     let lines = [line!() + 1, line!() + 2, line!() + 3];
+    ///     let _results = scope.results([stuck()]);
+    ///     let _joined = moro::join_all(scope, [stuck()]);
+    ///     let _errors = scope.validate_all_dedup([stuck()]);
+    ///     let jobs = scope.pending_jobs();
+    ///     let spawned_at: Vec<_> = jobs.iter().map(|job| job.location.line()).collect();
+    ///     assert_eq!(spawned_at, lines);
+    ///     assert!(jobs.iter().all(|job| job.location.file() == file!()));
+    ///     scope.terminate(()).await
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    pub fn pending_jobs(&self) -> Vec<JobInfo> {
+        let now = self.clock().now();
+        let mut jobs: Vec<JobInfo> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, id, entry)| entry.info(id, now))
+            .collect();
+        jobs.sort_by_key(|job| job.id);
+        jobs
+    }
+
+    /// Describes the jobs in the scope that have not completed, one per
+    /// line, in spawn order: each job's id, label (see
+    /// [`spawn_named`][Self::spawn_named]), spawn location and age.
+    ///
+    /// This is meant for logging when a scope appears stuck; use
+    /// [`pending_jobs`][Self::pending_jobs] to inspect the jobs
+    /// programmatically.
+    pub fn dump_pending(&self) -> String {
+        let mut dump = String::new();
+        for job in self.pending_jobs() {
+            let label = job.label.as_deref().unwrap_or("<unnamed>");
+            let _ = writeln!(
+                dump,
+                "job {} {label} spawned at {}, pending for {:?}",
+                job.id, job.location, job.age
+            );
+        }
+        dump
+    }
+
+    /// Returns the number of jobs in the scope that have not completed.
+    ///
+    /// The count is a snapshot: jobs may complete, or be spawned by other
+    /// jobs, as soon as it returns. It is meant for heuristics such as
+    /// backpressure, e.g. to stop spawning while many jobs are in flight.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     assert!(scope.is_empty());
+    ///     let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    ///     let waiting = scope.spawn(async { rx.await.unwrap() });
+    ///     assert_eq!(scope.len(), 1);
+    ///     tx.send(()).unwrap();
+    ///     waiting.await;
+    ///     assert_eq!(scope.len(), 0);
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    pub fn len(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+
+    /// Returns true if every job spawned into the scope has completed; a
+    /// snapshot, like [`len`][Self::len].
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Spawn a job that is cancelled when its handle is dropped.
+    ///
+    /// With [`spawn`][Self::spawn], dropping the returned handle does not
+    /// affect the job, which keeps running until it completes or the scope is
+    /// terminated. The [`CancelOnDrop`] handle returned here instead cancels
+    /// the job when dropped, before it has completed: the job is dropped at
+    /// its next `.await`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # futures::executor::block_on(async {
+    /// let (started, finished) = (AtomicBool::new(false), AtomicBool::new(false));
+    /// moro::async_scope!(|scope| {
+    ///     let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    ///     let job = scope.spawn_cancel_on_drop(async {
+    ///         started.store(true, Ordering::SeqCst);
+    ///         let _ = rx.await;
+    ///         finished.store(true, Ordering::SeqCst);
+    ///     });
+    ///     // Awaiting another job lets the scope start `job`.
+    ///     scope.spawn(async {}).await;
+    ///     assert!(started.load(Ordering::SeqCst));
+    ///     drop(job);
+    ///     // The job is dropped while it awaits `rx`, so it never finishes.
+    ///     tx.send(()).unwrap();
+    /// })
+    /// .await;
+    /// assert!(!finished.load(Ordering::SeqCst));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_cancel_on_drop<T>(
+        &'scope self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> CancelOnDrop<T>
+    where
+        T: 'scope + Send,
+    {
+        let job = self.spawn_job(Location::caller(), None, None, false, future);
+        CancelOnDrop::new(job.rx, job.abort)
+    }
+
+    /// Performs each of `sends` in turn while draining `drain` concurrently,
+    /// then drains the rest of `drain`. Returns the drained items.
+    ///
+    /// This avoids a deadlock that is easy to run into when feeding jobs
+    /// through bounded channels and collecting their results afterwards:
+    /// once the jobs block on delivering results that nobody consumes yet,
+    /// they stop receiving, their input channels fill up, and the sends block
+    /// forever. Here, items are drained whenever a send has to wait, so the
+    /// jobs keep making progress. See the `replicas` example.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let results = moro::scope(async |scope| {
+    ///     let (tx, rx) = async_channel::bounded::<u32>(1);
+    ///     scope.spawn_yielding(async move |yielder| {
+    ///         while let Ok(n) = rx.recv().await {
+    ///             yielder.yield_(n * 10).await;
+    ///         }
+    ///     });
+    ///     // `sends` owns the sender, so the channel closes once all messages
+    ///     // are sent, which ends the job.
+    ///     let sends = (0..5).map(move |n| {
+    ///         let tx = tx.clone();
+    ///         async move { tx.send(n).await.unwrap() }
+    ///     });
+    ///     let outputs = scope.outputs::<u32>();
+    ///     scope.send_all_then_drain(sends, outputs).await
+    /// })
+    /// .await;
+    /// assert_eq!(results, [0, 10, 20, 30, 40]);
+    /// # });
+    /// ```
+    pub async fn send_all_then_drain<T, F>(
+        &self,
+        sends: impl IntoIterator<Item = F>,
+        drain: impl AsyncIterator<Item = T>,
+    ) -> Vec<T>
+    where
+        F: Future<Output = ()>,
+    {
+        // As a stream, the drain keeps its pending `next` when a send wins.
+        let drain = futures::stream::unfold(drain, async |mut drain| {
+            let item = drain.next().await?;
+            Some((item, drain))
+        });
+        let mut drain = std::pin::pin!(drain);
+        let mut drained = vec![];
+        let mut drain_done = false;
+        for send in sends {
+            let mut send = std::pin::pin!(send);
+            while !drain_done {
+                match futures::future::select(send.as_mut(), StreamExt::next(&mut drain)).await {
+                    Either::Left(((), _)) => break,
+                    Either::Right((Some(item), _)) => drained.push(item),
+                    Either::Right((None, _)) => drain_done = true,
+                }
+            }
+            if drain_done {
+                send.await;
+            }
+        }
+        while let Some(item) = StreamExt::next(&mut drain).await {
+            drained.push(item);
+        }
+        drained
+    }
+
+    /// Spawn a job whose result can be awaited from several places.
+    ///
+    /// The returned [`SharedHandle`] can be cloned, e.g. to hand it to
+    /// sibling jobs, and each clone yields a clone of the job's result. The
+    /// job runs once, and the scope keeps it running to completion however
+    /// many clones are outstanding.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # futures::executor::block_on(async {
+    /// let runs = AtomicUsize::new(0);
+    /// let result = moro::async_scope!(|scope| {
+    ///     let config = scope.spawn_shared(async {
+    ///         runs.fetch_add(1, Ordering::SeqCst);
+    ///         String::from("config")
+    ///     });
+    ///     let a = scope.spawn({
+    ///         let config = config.clone();
+    ///         async move { format!("a uses {}", config.await) }
+    ///     });
+    ///     let b = scope.spawn({
+    ///         let config = config.clone();
+    ///         async move { format!("b uses {}", config.await) }
+    ///     });
+    ///     (a.await, b.await, config.await)
+    /// })
+    /// .await;
+    /// assert_eq!(result.0, "a uses config");
+    /// assert_eq!(result.1, "b uses config");
+    /// assert_eq!(result.2, "config");
+    /// assert_eq!(runs.load(Ordering::SeqCst), 1);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_shared<T>(
+        &'scope self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> SharedHandle<T>
+    where
+        T: 'scope + Send + Clone,
+    {
+        SharedHandle::new(
+            self.spawn_job(Location::caller(), None, None, false, future)
+                .rx,
+        )
+    }
+
+    /// Creates a channel to broadcast messages to the jobs of the scope.
+    ///
+    /// Returns the sender, typically moved into a producer job, and a
+    /// function creating receivers. Each message sent is delivered (cloned)
+    /// to every receiver that exists at the time; receivers created later do
+    /// not see earlier messages. This is the fan-out complement to collecting
+    /// the results of many jobs, e.g. to send the same request to all replicas.
+    ///
+    /// Sending never waits for slow receivers. Instead, each receiver buffers
+    /// up to `capacity` messages; when a message arrives at a full buffer, the
+    /// oldest buffered message is dropped, and the receiver *lags*. The number
+    /// of messages a receiver missed this way is reported by
+    /// [`BroadcastReceiver::missed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use moro::AsyncIterator;
+    /// # futures::executor::block_on(async {
+    /// let received = moro::async_scope!(|scope| {
+    ///     let (tx, subscribe) = scope.broadcast(4);
+    ///     let replicas: Vec<_> = (0..3)
+    ///         .map(|i| {
+    ///             let mut rx = subscribe();
+    ///             scope.spawn(async move {
+    ///                 let mut received = vec![];
+    ///                 while let Some(msg) = rx.next().await {
+    ///                     received.push(format!("{i}: {msg}"));
+    ///                 }
+    ///                 received
+    ///             })
+    ///         })
+    ///         .collect();
+    ///     scope.spawn(async move {
+    ///         for msg in ["a", "b"] {
+    ///             tx.send(msg);
+    ///         }
+    ///     });
+    ///     let mut received = vec![];
+    ///     for replica in replicas {
+    ///         received.extend(replica.await);
+    ///     }
+    ///     received
+    /// })
+    /// .await;
+    /// assert_eq!(received, ["0: a", "0: b", "1: a", "1: b", "2: a", "2: b"]);
+    /// # });
+    /// ```
+    ///
+    /// A receiver that falls behind misses the oldest messages:
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let (tx, subscribe) = scope.broadcast(2);
+    ///     let mut rx = subscribe();
+    ///     for i in 0..5 {
+    ///         tx.send(i);
+    ///     }
+    ///     drop(tx);
+    ///     assert_eq!(rx.recv().await, Some(3));
+    ///     assert_eq!(rx.recv().await, Some(4));
+    ///     assert_eq!(rx.recv().await, None);
+    ///     assert_eq!(rx.missed(), 3);
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    pub fn broadcast<T>(
+        &self,
+        capacity: usize,
+    ) -> (
+        BroadcastSender<T>,
+        impl Fn() -> BroadcastReceiver<T> + Clone + Send + Sync,
+    )
+    where
+        T: Clone + Send,
+    {
+        broadcast::channel(capacity)
+    }
+
+    /// Returns a handle whose [`spawn`][LimitedScope::spawn] caps the number
+    /// of its jobs running at once to `limit` (at least one).
+    ///
+    /// Jobs beyond the limit wait in a queue and start in the order they
+    /// were spawned, one as each earlier job completes; a queued job is only
+    /// polled again once it is its turn to start. Awaiting them works as
+    /// usual. Terminating the scope drops the queued jobs. Each call creates
+    /// an independent limit; clone the handle to share one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use moro::test_util::yield_now;
+    /// # futures::executor::block_on(async {
+    /// let running = AtomicUsize::new(0);
+    /// let max_running = AtomicUsize::new(0);
+    /// let fetched = moro::async_scope!(|scope| {
+    ///     let limited = scope.with_concurrency_limit(3);
+    ///     let fetches: Vec<_> = (0..10)
+    ///         .map(|page| {
+    ///             let (running, max_running) = (&running, &max_running);
+    ///             limited.spawn(async move {
+    ///                 let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+    ///                 max_running.fetch_max(now, Ordering::SeqCst);
+    ///                 for _ in 0..3 {
+    ///                     yield_now().await;
+    ///                 }
+    ///                 running.fetch_sub(1, Ordering::SeqCst);
+    ///                 page
+    ///             })
+    ///         })
+    ///         .collect();
+    ///     futures::future::join_all(fetches).await
+    /// })
+    /// .await;
+    /// assert_eq!(fetched, (0..10).collect::<Vec<_>>());
+    /// assert_eq!(max_running.into_inner(), 3);
+    /// # });
+    /// ```
+    ///
+    /// Queued jobs start in the order they were spawned:
+    ///
+    /// ```rust
+    /// # use std::sync::Mutex;
+    /// # use moro::test_util::yield_now;
+    /// # futures::executor::block_on(async {
+    /// let started = Mutex::new(vec![]);
+    /// moro::async_scope!(|scope| {
+    ///     let limited = scope.with_concurrency_limit(2);
+    ///     for job in 0..6 {
+    ///         let started = &started;
+    ///         limited.spawn(async move {
+    ///             started.lock().unwrap().push(job);
+    ///             for _ in 0..(6 - job) {
+    ///                 yield_now().await;
+    ///             }
+    ///         });
+    ///     }
+    /// })
+    /// .await;
+    /// assert_eq!(started.into_inner().unwrap(), [0, 1, 2, 3, 4, 5]);
+    /// # });
+    /// ```
+    pub fn with_concurrency_limit(&'scope self, limit: usize) -> LimitedScope<'scope, 'env, R> {
+        LimitedScope::new(self, limit)
+    }
+
+    /// Spawn a job whose start is subject to an adaptive concurrency limit.
+    ///
+    /// At most [`adaptive_limit`][Self::adaptive_limit] jobs spawned with this
+    /// method run at once; the others wait to be admitted. The limit tunes
+    /// itself based on how long jobs take from admission to completion,
+    /// measured with the scope's [`Clock`]: it grows while jobs complete
+    /// quickly and backs off when they slow down, which suits workloads whose
+    /// best concurrency is unknown (e.g. calls to a backend with varying
+    /// capacity). See [`AdaptiveConfig`] for the policy and its parameters,
+    /// which are set with [`ScopeBody::with_adaptive`][crate::ScopeBody::with_adaptive].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::{sync::Arc, time::Duration};
+    /// # use moro::test_util::VirtualClock;
+    /// # futures::executor::block_on(async {
+    /// let clock = Arc::new(VirtualClock::new());
+    /// let config = moro::AdaptiveConfig {
+    ///     initial: 1,
+    ///     min: 1,
+    ///     max: 8,
+    ///     target_latency: Duration::from_millis(50),
+    /// };
+    /// // Simulates a call to a backend taking `millis` to respond.
+    /// let call = |millis| {
+    ///     let clock = &clock;
+    ///     async move { clock.advance(Duration::from_millis(millis)) }
+    /// };
+    /// moro::async_scope!(|scope| {
+    ///     for _ in 0..3 {
+    ///         scope.spawn_adaptive(call(10)).await;
+    ///     }
+    ///     assert_eq!(scope.adaptive_limit(), 4);
+    ///
+    ///     scope.spawn_adaptive(call(200)).await;
+    ///     assert_eq!(scope.adaptive_limit(), 2);
+    /// })
+    /// .with_clock(clock.clone())
+    /// .with_adaptive(config)
+    /// .await;
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_adaptive<T>(
+        &'scope self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send + 'scope>
+    where
+        T: 'scope + Send,
+    {
+        let clock = self.clock();
+        self.spawn(async move {
+            let mut permit = self.adaptive.acquire().await;
+            let start = clock.now();
+            let value = future.await;
+            permit.latency = Some(clock.now().saturating_duration_since(start));
+            value
+        })
+    }
+
+    /// Spawn a job that starts no faster than the scope's rate limit allows.
+    ///
+    /// All jobs spawned with this method share a token bucket, configured
+    /// with [`ScopeBody::with_rate_limit`][crate::ScopeBody::with_rate_limit],
+    /// which holds up to `burst` tokens and is refilled with `rate` tokens
+    /// per second; it starts full. Each job takes a token before it starts,
+    /// waiting for one if the bucket is empty, so that jobs start at most
+    /// `rate` times per second on average, with bursts of up to `burst` jobs.
+    /// This suits e.g. clients of APIs that limit the rate of requests. Jobs
+    /// are started in the order they were first polled. If the scope has no
+    /// rate limit, the jobs start right away.
+    ///
+    /// Time is measured, and waited for, with the scope's [`Clock`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::{sync::Arc, time::Duration};
+    /// # use moro::test_util::VirtualClock;
+    /// # futures::executor::block_on(async {
+    /// // A simulated clock, whose time jumps ahead when a job waits.
+    /// let clock = Arc::new(VirtualClock::new());
+    /// let start = moro::Clock::now(&clock);
+    /// let started = moro::async_scope!(|scope| {
+    ///     let jobs: Vec<_> = (0..5)
+    ///         .map(|_| {
+    ///             let clock = &clock;
+    ///             scope.spawn_rate_limited(async move { moro::Clock::now(clock) - start })
+    ///         })
+    ///         .collect();
+    ///     futures::future::join_all(jobs).await
+    /// })
+    /// .with_clock(clock.clone())
+    /// .with_rate_limit(2.0, 2)
+    /// .await;
+    /// let millis = |ms| Duration::from_millis(ms);
+    /// assert_eq!(started, [millis(0), millis(0), millis(500), millis(1000), millis(1500)]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_rate_limited<T>(
+        &'scope self,
+        future: impl Future<Output = T> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send + 'scope>
+    where
+        T: 'scope + Send,
+    {
+        let clock = self.clock();
+        self.spawn(async move {
+            let start = self.rate_limiter.reserve(clock.now());
+            if start > clock.now() {
+                clock.sleep_until(start).await;
+            }
+            future.await
+        })
+    }
+
+    /// Spawn a job that retries a fallible operation according to `policy`.
+    ///
+    /// `make` is called to start each attempt. If an attempt fails and the
+    /// policy allows another one, the job waits for the policy's next delay,
+    /// measured with the scope's [`Clock`], and tries again. The job yields
+    /// the first success, or the error of the last attempt.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::{sync::{Arc, Mutex}, time::Duration};
+    /// # use moro::test_util::VirtualClock;
+    /// use moro::{Jitter, RetryPolicy};
+    ///
+    /// # futures::executor::block_on(async {
+    /// let clock = Arc::new(VirtualClock::new());
+    /// let start = moro::Clock::now(&clock);
+    /// let policy = RetryPolicy::new(4, Duration::from_millis(100))
+    ///     .with_jitter(Jitter::Equal)
+    ///     .with_seed(22);
+    /// let attempts = Mutex::new(vec![]);
+    /// let result = moro::async_scope!(|scope| {
+    ///     scope
+    ///         .spawn_retry(policy.clone(), || async {
+    ///             let mut attempts = attempts.lock().unwrap();
+    ///             attempts.push(moro::Clock::now(&clock) - start);
+    ///             if attempts.len() < 3 { Err("unavailable") } else { Ok(attempts.len()) }
+    ///         })
+    ///         .await
+    /// })
+    /// .with_clock(clock.clone())
+    /// .await;
+    /// assert_eq!(result, Ok(3));
+    ///
+    /// // The attempts were spaced out by the policy's first two delays.
+    /// let delays: Vec<_> = policy.delays().collect();
+    /// let attempts = attempts.into_inner().unwrap();
+    /// assert_eq!(attempts, [Duration::ZERO, delays[0], delays[0] + delays[1]]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_retry<T, E, F>(
+        &'scope self,
+        policy: RetryPolicy,
+        mut make: impl FnMut() -> F + Send + 'scope,
+    ) -> Spawned<impl Future<Output = Result<T, E>> + Send + 'scope>
+    where
+        F: Future<Output = Result<T, E>> + Send + 'scope,
+        T: 'scope + Send,
+        E: 'scope + Send,
+    {
+        let clock = self.clock();
+        self.spawn(async move {
+            let mut delays = policy.delays();
+            loop {
+                let error = match make().await {
+                    Ok(value) => return Ok(value),
+                    Err(error) => error,
+                };
+                match delays.next() {
+                    Some(delay) => clock.sleep_until(clock.now() + delay).await,
+                    None => return Err(error),
+                }
+            }
+        })
+    }
+
+    /// The current concurrency limit of jobs spawned with
+    /// [`spawn_adaptive`][Self::spawn_adaptive]. This is a snapshot; the limit
+    /// changes as adaptive jobs complete.
+    pub fn adaptive_limit(&self) -> usize {
+        self.adaptive.limit()
+    }
+}
+
+impl<'scope, 'env, T: Send> Scope<'scope, 'env, Vec<T>> {
+    /// Spawns each of `jobs` and collects their results as they complete,
+    /// stopping early once a result satisfies `stop`.
+    ///
+    /// This is "search and stop when found" that also keeps the partial
+    /// results gathered so far. When a result satisfies `stop`, the scope is
+    /// terminated with the results collected up to and including that one,
+    /// dropping the remaining jobs (and any others in the scope). Otherwise
+    /// the returned future yields every result, in completion order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// # use moro::test_util::yield_now;
+    /// let results = moro::async_scope!(|scope| {
+    ///     scope
+    ///         .collect_until(
+    ///             (0..10).map(|i| async move {
+    ///                 // Later jobs take longer, so results arrive in order.
+    ///                 for _ in 0..i {
+    ///                     yield_now().await;
+    ///                 }
+    ///                 i
+    ///             }),
+    ///             |&i| i == 4,
+    ///         )
+    ///         .await
+    /// })
+    /// .await;
+    /// assert_eq!(results, [0, 1, 2, 3, 4]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn collect_until<J>(
+        &'scope self,
+        jobs: impl IntoIterator<Item = J>,
+        stop: impl Fn(&T) -> bool + Send + 'scope,
+    ) -> impl Future<Output = Vec<T>> + 'scope
+    where
+        T: 'scope,
+        J: Future<Output = T> + Send + 'scope,
+    {
+        let mut set = self.results(jobs);
+        async move {
+            let mut results = vec![];
+            while let Some(result) = set.next().await {
+                let found = stop(&result);
+                results.push(result);
+                if found {
+                    return self.terminate(results).await;
+                }
+            }
+            results
+        }
+    }
+}
+
+impl<'scope, 'env, O: Send, E: Send> Scope<'scope, 'env, Result<O, E>> {
+    /// Spawn a job whose error, if any, terminates the scope.
+    ///
+    /// This is shorthand for `scope.spawn(future).or_cancel(scope)`: if the
+    /// job returns `Err(e)`, the scope is terminated with `Err(e)`, and the
+    /// returned handle yields the `Ok` value otherwise. Like any job, it runs
+    /// whether or not the handle is awaited.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     for i in [1, 2, -3, 4] {
+    ///         scope.spawn_checked(async move {
+    ///             if i < 0 {
+    ///                 Err(format!("negative: {i}"))
+    ///             } else {
+    ///                 Ok(())
+    ///             }
+    ///         });
+    ///     }
+    ///     let doubled = scope.spawn_checked(async { Ok(11 * 2) }).await;
+    ///     Ok(doubled)
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err("negative: -3".to_string()));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_checked<T>(
+        &'scope self,
+        future: impl Future<Output = Result<T, E>> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send + 'scope>
+    where
+        T: 'scope + Send,
+    {
+        self.spawn(async move {
+            match future.await {
+                Ok(value) => value,
+                Err(error) => self.terminate(Err(error)).await,
+            }
+        })
+    }
+
+    /// Spawn a job whose errors *and panics* cancel the scope.
+    ///
+    /// If the job returns `Err(e)`, the scope is terminated with `Err(e)`,
+    /// just as with [`Spawned::or_cancel`].
+    /// If the job panics, the panic is caught rather than unwound through the
+    /// scope, and the scope is terminated with `Err(E::from(payload))`. This
+    /// lets e.g. a server treat a panicking handler as an ordinary error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::any::Any;
+    /// #[derive(Debug, PartialEq)]
+    /// enum Error {
+    ///     Failed(u32),
+    ///     Panicked(String),
+    /// }
+    ///
+    /// impl From<Box<dyn Any + Send>> for Error {
+    ///     fn from(payload: Box<dyn Any + Send>) -> Self {
+    ///         let message = payload.downcast::<&str>().map(|s| s.to_string());
+    ///         Error::Panicked(message.unwrap_or_default())
+    ///     }
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let result: Result<(), Error> = moro::async_scope!(|scope| {
+    ///     scope.spawn_catching(async { Err::<(), _>(Error::Failed(22)) }).await;
+    ///     Ok(())
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err(Error::Failed(22)));
+    ///
+    /// let result: Result<(), Error> = moro::async_scope!(|scope| {
+    ///     scope
+    ///         .spawn_catching(async { panic!("boom") as Result<(), Error> })
+    ///         .await;
+    ///     Ok(())
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err(Error::Panicked("boom".to_string())));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn spawn_catching<T>(
+        &'scope self,
+        future: impl Future<Output = Result<T, E>> + Send + 'scope,
+    ) -> Spawned<impl Future<Output = T> + Send + 'scope>
+    where
+        T: 'scope + Send,
+        E: From<Box<dyn Any + Send>>,
+    {
+        self.spawn(async move {
+            match AssertUnwindSafe(future).catch_unwind().await {
+                Ok(Ok(value)) => value,
+                Ok(Err(error)) => self.terminate(Err(error)).await,
+                Err(payload) => self.terminate(Err(E::from(payload))).await,
+            }
+        })
+    }
+}
+
+/// Splits `f` into a closure that runs it, to be handed to another thread, and
+/// a future of its result, which resumes any panic of `f`.
+fn offload<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> (
+    impl FnOnce() + Send + 'static,
+    impl Future<Output = T> + Send,
+) {
+    let (tx, rx) = oneshot::channel();
+    let run = move || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+        let _ = tx.send(result);
+    };
+    let result = async move {
+        match rx.await {
+            Ok(Ok(value)) => value,
+            Ok(Err(panic)) => std::panic::resume_unwind(panic),
+            Err(oneshot::Canceled) => unreachable!("offloaded job dropped its result"),
+        }
+    };
+    (run, result)
+}
+
+/// Items of a stream polled by a job, see [`Scope::stream_within_scope`].
+struct ScopedStream<T> {
+    rx: async_channel::Receiver<T>,
+
+    /// Drops the stream along with the iterator, even while it is pending.
+    _job: CancelOnDrop<()>,
+}
+
+impl<T> AsyncIterator for ScopedStream<T> {
+    type Item = T;
+
+    async fn next(&mut self) -> Option<T> {
+        self.rx.recv().await.ok()
+    }
 }
@@ -1,18 +1,83 @@
-use std::pin::Pin;
+use std::{convert::Infallible, pin::Pin, sync::Arc, time::Duration};
 
-use futures::Future;
+use futures::{future::Either, Future};
 use pin_project::pin_project;
 
-use crate::body::Body;
+use crate::{body::Body, AdaptiveConfig, Clock, PendingJob, Scope};
 
+/// The future for a scope, returned by [`async_scope!`][crate::async_scope],
+/// [`scope`][crate::scope] and friends. Awaiting it runs the scope to completion.
+///
+/// `ScopeBody` is `Unpin` whenever the body future is, which is the case for
+/// scopes created with `async_scope!` or [`scope_fn`][crate::scope_fn], which
+/// box their body; those can be passed directly to combinators like
+/// [`select`](futures::future::select). Scopes created with
+/// [`scope`][crate::scope] avoid boxing their body and are not `Unpin`; pin
+/// them with [`std::pin::pin!`] or [`pinned`][Self::pinned].
+///
+/// # Examples
+///
+/// ```rust
+/// # use futures::future::{self, Either};
+/// # futures::executor::block_on(async {
+/// let shutdown = future::ready(());
+/// let scope = moro::async_scope!(|scope| {
+///     scope.spawn(future::pending::<()>()).await;
+/// });
+/// match future::select(scope, shutdown).await {
+///     Either::Left(((), _)) => unreachable!("the scope never completes"),
+///     Either::Right(((), _)) => {} // shut down; the scope is cancelled
+/// }
+/// # });
+/// ```
+///
+/// Jobs that become ready together wake the task polling the scope only
+/// once, rather than once per job:
+///
+/// ```rust
+/// # use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+/// # use std::task::Context;
+/// # use futures::{channel::oneshot, task::{self, ArcWake}, FutureExt};
+/// struct CountWakes(AtomicUsize);
+///
+/// impl ArcWake for CountWakes {
+///     fn wake_by_ref(this: &Arc<Self>) {
+///         this.0.fetch_add(1, Ordering::SeqCst);
+///     }
+/// }
+///
+/// let (senders, receivers): (Vec<_>, Vec<_>) =
+///     (0..100).map(|_| oneshot::channel::<()>()).unzip();
+/// let mut scope = moro::async_scope!(|scope| {
+///     for rx in receivers {
+///         scope.spawn(async { rx.await.unwrap() });
+///     }
+/// });
+///
+/// let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+/// let waker = task::waker(wakes.clone());
+/// let mut cx = Context::from_waker(&waker);
+/// assert!(scope.poll_unpin(&mut cx).is_pending());
+///
+/// for tx in senders {
+///     tx.send(()).unwrap();
+/// }
+/// assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+///
+/// // A single poll then runs all of the jobs to completion.
+/// assert!(scope.poll_unpin(&mut cx).is_ready());
+/// ```
 #[pin_project]
-pub struct ScopeBody<'env, R: 'env, F>
+pub struct ScopeBody<'env, R, F>
 where
     R: Send,
     F: Future<Output = R>,
 {
     #[pin]
     body: Body<'env, 'env, R, F>,
+
+    /// Set once the scope has produced its result.
+    completed: bool,
 }
 
 impl<'env, R, F> ScopeBody<'env, R, F>
@@ -21,7 +86,323 @@ where
     F: Future<Output = R>,
 {
     pub(crate) fn new(body: Body<'env, 'env, R, F>) -> Self {
-        Self { body }
+        Self {
+            body,
+            completed: false,
+        }
+    }
+
+    /// Pins the scope on the heap, so that it can be used with combinators
+    /// that require `Unpin` futures, such as
+    /// [`select`](futures::future::select) or `futures::select!`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use futures::future::{self, Either};
+    /// # futures::executor::block_on(async {
+    /// let timeout = future::ready(());
+    /// let scope = moro::scope(async |scope| {
+    ///     scope.spawn(future::pending::<()>()).await;
+    /// });
+    /// let result = future::select(scope.pinned(), timeout).await;
+    /// assert!(matches!(result, Either::Right(((), _))));
+    /// # });
+    /// ```
+    pub fn pinned(self) -> Pin<Box<Self>> {
+        Box::pin(self)
+    }
+
+    /// Returns a future that resolves once no job of the scope runs anymore:
+    /// when the scope has completed, or was terminated or dropped.
+    ///
+    /// Awaiting the scope itself already waits for all of its jobs, not just
+    /// its body, before yielding the result. `completion` provides the same
+    /// barrier to *other* code, e.g. a task that must wait until all of the
+    /// scope's side effects are done while the scope is awaited elsewhere.
+    /// The returned future does not borrow the scope.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::Mutex;
+    /// # futures::executor::block_on(async {
+    /// let log = Mutex::new(vec![]);
+    /// let scope = moro::async_scope!(|scope| {
+    ///     scope.spawn(async { log.lock().unwrap().push("side effect") });
+    ///     "result"
+    /// });
+    /// let completion = scope.completion();
+    /// let (result, ()) = futures::join!(scope, async {
+    ///     completion.await;
+    ///     assert_eq!(*log.lock().unwrap(), ["side effect"]);
+    /// });
+    /// assert_eq!(result, "result");
+    /// # });
+    /// ```
+    pub fn completion(&self) -> impl Future<Output = ()> + Send + Sync + 'static {
+        self.body.scope().completed()
+    }
+
+    /// Installs `clock` as the scope's source of time, replacing the
+    /// default [`SystemClock`][crate::SystemClock].
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        self.body.scope().set_clock(Arc::new(clock));
+        self
+    }
+
+    /// Sets `context` as the scope's context value of its type, readable by
+    /// the body and every job with [`Scope::context`][crate::Scope::context].
+    pub fn with_context<C: Clone + Send + Sync + 'static>(self, context: C) -> Self {
+        self.body.scope().contexts().insert(context);
+        self
+    }
+
+    /// Makes the context values of `parent` available in this scope, except
+    /// for types set with [`with_context`][Self::with_context] before.
+    /// Values set afterwards take precedence.
+    pub fn inherit_context<R2: Send>(self, parent: &Scope<'_, '_, R2>) -> Self {
+        self.body.scope().contexts().inherit(parent.contexts());
+        self
+    }
+
+    /// Limits the number of jobs spawned with
+    /// [`Scope::spawn_prioritized`][crate::Scope::spawn_prioritized] that may be
+    /// in flight at once; see that method for how the budget is enforced.
+    pub fn with_budget(self, budget: usize) -> Self {
+        self.body.scope().set_budget(budget);
+        self
+    }
+
+    /// Configures the concurrency limiter of
+    /// [`Scope::spawn_adaptive`][crate::Scope::spawn_adaptive].
+    pub fn with_adaptive(self, config: AdaptiveConfig) -> Self {
+        self.body.scope().set_adaptive(config);
+        self
+    }
+
+    /// Limits the rate at which jobs spawned with
+    /// [`Scope::spawn_rate_limited`][crate::Scope::spawn_rate_limited] start
+    /// to `rate` per second, with bursts of up to `burst` jobs; see that
+    /// method for how the limit is enforced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not positive and finite.
+    pub fn with_rate_limit(self, rate: f64, burst: usize) -> Self {
+        self.body.scope().set_rate_limit(rate, burst);
+        self
+    }
+
+    /// Runs the scope until it completes or `disconnect` resolves, whichever
+    /// comes first, returning `None` in the latter case.
+    ///
+    /// This ties the scope to the lifetime of something external, typically
+    /// an HTTP request: pass a future that resolves when the client hangs up,
+    /// and all of the scope's jobs (database queries, upstream calls, ...)
+    /// are cancelled as soon as nobody is waiting for the response. See also
+    /// [`request_scope`][crate::request_scope].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let (hangup, disconnected) = futures::channel::oneshot::channel::<()>();
+    /// let response = moro::async_scope!(|scope| {
+    ///     scope.spawn(async { drop(hangup) });
+    ///     // Never completes; cancelled when the client disconnects.
+    ///     scope.spawn(futures::future::pending::<String>()).await
+    /// })
+    /// .linked_to(async { drop(disconnected.await) })
+    /// .await;
+    /// assert_eq!(response, None);
+    /// # });
+    /// ```
+    pub async fn linked_to(self, disconnect: impl Future<Output = ()>) -> Option<R> {
+        match futures::future::select(std::pin::pin!(self), std::pin::pin!(disconnect)).await {
+            Either::Left((result, _)) => Some(result),
+            Either::Right(((), _)) => None,
+        }
+    }
+
+    /// Runs the scope with a time budget: if the body and its jobs have not
+    /// all finished `timeout` after the scope is first polled, the scope is
+    /// dropped -- cancelling its jobs -- and `on_timeout()` is returned
+    /// instead of the scope's result.
+    ///
+    /// Time is measured with the scope's [`Clock`], so the budget follows
+    /// [`with_clock`][Self::with_clock] if one is installed. If the scope
+    /// completes in the same poll as the deadline passes, the scope's own
+    /// result wins.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| -> Result<u32, &str> {
+    ///     let slow = scope.spawn(futures::future::pending::<u32>());
+    ///     Ok(slow.await)
+    /// })
+    /// .with_timeout(Duration::from_millis(10), || Err("timed out"))
+    /// .await;
+    /// assert_eq!(result, Err("timed out"));
+    /// # });
+    /// ```
+    pub async fn with_timeout(self, timeout: Duration, on_timeout: impl FnOnce() -> R) -> R {
+        let clock = self.body.scope().clock();
+        let deadline = clock.sleep_until(clock.now() + timeout);
+        match futures::future::select(std::pin::pin!(self), deadline).await {
+            Either::Left((result, _)) => result,
+            Either::Right(((), _)) => on_timeout(),
+        }
+    }
+
+    /// Turns the scope into a [`Stream`](futures::Stream) of the results of
+    /// its jobs of type `T`, in the order the jobs complete. Polling the
+    /// stream drives the scope; the stream ends once the body and all jobs
+    /// have completed and every result has been taken. The body's own result
+    /// is then available from [`ScopeResults::take_output`].
+    ///
+    /// Jobs opt in by being spawned with
+    /// [`Scope::spawn_streamed`][crate::Scope::spawn_streamed] instead of
+    /// [`Scope::spawn`][crate::Scope::spawn]; values yielded with
+    /// [`Scope::spawn_yielding`][crate::Scope::spawn_yielding] are streamed as
+    /// well. Results are routed by type, hence `T: 'static`. This lets a scope
+    /// feed combinators like
+    /// [`StreamExt::for_each`](futures::StreamExt::for_each) directly. The
+    /// stream should be the only consumer of the scope's
+    /// [`outputs`][crate::Scope::outputs] of type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::StreamExt;
+    /// # futures::executor::block_on(async {
+    /// let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    /// let scope = moro::scope(async |scope| {
+    ///     scope.spawn_streamed(async {
+    ///         rx.await.unwrap();
+    ///         "slow"
+    ///     });
+    ///     scope.spawn_streamed(async { "quick" });
+    ///     scope.spawn(async { tx.send(()).unwrap() });
+    ///     "body"
+    /// });
+    /// let mut results = std::pin::pin!(scope.results::<&str>());
+    /// let mut order = vec![];
+    /// (&mut results)
+    ///     .for_each(|r| {
+    ///         order.push(r);
+    ///         futures::future::ready(())
+    ///     })
+    ///     .await;
+    /// assert_eq!(order, ["quick", "slow"]);
+    /// assert_eq!(results.take_output(), Some("body"));
+    /// # });
+    /// ```
+    pub fn results<T: Send + 'static>(self) -> ScopeResults<'env, R, F, T>
+    where
+        F: 'env,
+    {
+        ScopeResults {
+            scope: self,
+            done: false,
+            output: None,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Runs the scope until it completes or `deadline` resolves, whichever
+    /// comes first.
+    ///
+    /// If the scope completes first, its result is returned as `Some`. If the
+    /// deadline comes first, the scope is dropped -- cancelling all of its
+    /// jobs -- and `None` is returned together with the jobs spawned with
+    /// [`Scope::spawn_restartable`][crate::Scope::spawn_restartable] that did
+    /// not finish, in spawn order. They can be persisted and resumed later,
+    /// so that interrupting a batch loses no work. Jobs spawned in other ways
+    /// are simply cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::{atomic::{AtomicBool, Ordering}, Mutex};
+    /// # futures::executor::block_on(async {
+    /// let resumed = AtomicBool::new(false);
+    /// let done = Mutex::new(vec![]);
+    /// let (resumed, done) = (&resumed, &done);
+    ///
+    /// let (result, pending) = moro::async_scope!(|scope| {
+    ///     for i in 0..3 {
+    ///         scope.spawn_restartable(move || async move {
+    ///             // Job 0 is quick; the others only finish once resumed.
+    ///             if i > 0 && !resumed.load(Ordering::SeqCst) {
+    ///                 futures::future::pending::<()>().await;
+    ///             }
+    ///             done.lock().unwrap().push(i);
+    ///         });
+    ///     }
+    /// })
+    /// .drain_or_recover(futures::future::ready(()))
+    /// .await;
+    /// assert_eq!(result, None);
+    /// assert_eq!(pending.len(), 2);
+    /// assert_eq!(*done.lock().unwrap(), [0]);
+    ///
+    /// resumed.store(true, Ordering::SeqCst);
+    /// moro::async_scope!(|scope| {
+    ///     for job in &pending {
+    ///         scope.spawn(job.start());
+    ///     }
+    /// })
+    /// .await;
+    /// assert_eq!(*done.lock().unwrap(), [0, 1, 2]);
+    /// # });
+    /// ```
+    pub async fn drain_or_recover(
+        self,
+        deadline: impl Future<Output = ()>,
+    ) -> (Option<R>, Vec<PendingJob<'env>>) {
+        let mut this = std::pin::pin!(self);
+        match futures::future::select(this.as_mut(), std::pin::pin!(deadline)).await {
+            Either::Left((result, _)) => (Some(result), vec![]),
+            Either::Right(((), _)) => (None, this.body.scope().take_restartable()),
+        }
+    }
+}
+
+impl<'env, T, F> ScopeBody<'env, Result<T, Infallible>, F>
+where
+    T: Send,
+    F: Future<Output = Result<T, Infallible>>,
+{
+    /// Marks a scope whose body returns a `Result` as never failing, and
+    /// returns its `Ok` value directly.
+    ///
+    /// A body that only returns `Ok(..)` and never terminates the scope with
+    /// an error leaves the error type unconstrained, which fails to compile.
+    /// Calling `infallible` fixes the error type to [`Infallible`], so the
+    /// scope can only be terminated with `Ok(..)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     let job = scope.spawn(async { 22 });
+    ///     Ok(job.await * 2)
+    /// })
+    /// .infallible()
+    /// .await;
+    /// assert_eq!(result, 44);
+    /// # });
+    /// ```
+    pub async fn infallible(self) -> T {
+        match self.await {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
     }
 }
 
@@ -36,6 +417,108 @@ where
         self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
-        Pin::new(&mut self.project().body).poll(cx)
+        let this = self.project();
+        let poll = this.body.poll(cx);
+        *this.completed |= poll.is_ready();
+        poll
+    }
+}
+
+/// A scope is terminated, in the sense of
+/// [`FusedFuture`](futures::future::FusedFuture), once it has produced its
+/// result, so it can be used in `futures::select!` without
+/// [`fuse`](futures::FutureExt::fuse).
+///
+/// # Examples
+///
+/// ```rust
+/// # futures::executor::block_on(async {
+/// let mut scope = std::pin::pin!(moro::async_scope!(|scope| scope.spawn(async { 22 }).await));
+/// let mut shutdown = futures::future::pending::<()>();
+/// let result = futures::select! {
+///     result = scope => result,
+///     () = shutdown => unreachable!(),
+/// };
+/// assert_eq!(result, 22);
+/// assert!(futures::future::FusedFuture::is_terminated(&scope));
+/// # });
+/// ```
+impl<'env, R, F> futures::future::FusedFuture for ScopeBody<'env, R, F>
+where
+    R: Send,
+    F: Future<Output = R>,
+{
+    fn is_terminated(&self) -> bool {
+        self.completed
+    }
+}
+
+/// The stream of job results returned by [`ScopeBody::results`].
+#[pin_project]
+pub struct ScopeResults<'env, R, F, T>
+where
+    R: Send,
+    F: Future<Output = R>,
+{
+    #[pin]
+    scope: ScopeBody<'env, R, F>,
+
+    /// Set once the scope has completed; only buffered values remain.
+    done: bool,
+
+    /// The body's result, once the scope has completed.
+    output: Option<R>,
+    phantom: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<R, F, T> ScopeResults<'_, R, F, T>
+where
+    R: Send,
+    F: Future<Output = R>,
+{
+    /// Takes the result of the scope's body, once the stream has ended.
+    /// Returns `None` before that, or if it was already taken.
+    pub fn take_output(self: Pin<&mut Self>) -> Option<R> {
+        self.project().output.take()
+    }
+}
+
+impl<R, F, T> futures::Stream for ScopeResults<'_, R, F, T>
+where
+    R: Send,
+    F: Future<Output = R>,
+    T: Send + 'static,
+{
+    type Item = T;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<T>> {
+        use std::task::Poll;
+
+        let mut this = self.project();
+
+        // The values are only yielded by jobs, which run while we poll the
+        // scope below, so there is no need to wait on the channel itself.
+        let rx = crate::outputs::receiver::<T>(this.scope.body.scope().output_table());
+        if let Ok(value) = rx.try_recv() {
+            return Poll::Ready(Some(value));
+        }
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        if let Poll::Ready(output) = this.scope.as_mut().poll(cx) {
+            *this.output = Some(output);
+            *this.done = true;
+        }
+        // A job may have replaced the channel, see `Scope::outputs`.
+        let rx = crate::outputs::receiver::<T>(this.scope.body.scope().output_table());
+        match rx.try_recv() {
+            Ok(value) => Poll::Ready(Some(value)),
+            Err(_) if *this.done => Poll::Ready(None),
+            Err(_) => Poll::Pending,
+        }
     }
 }
@@ -0,0 +1,55 @@
+/// Limit on the jobs in flight at which the body of a self-limiting scope
+/// is held back, see [`crate::scope_fn_self_limiting`].
+///
+/// The limit is tuned by comparing the rate at which jobs are spawned with
+/// the rate at which they complete, over windows of `limit` completions. If
+/// the body was held back during a window and still spawned jobs faster
+/// than they completed, the limit shrinks by a quarter; if it was never held
+/// back, the limit grows by one.
+pub(crate) struct SelfLimit {
+    limit: usize,
+
+    /// Number of jobs spawned and completed when the window started.
+    window_spawned: u64,
+    window_completed: u64,
+
+    /// Whether the body was held back during the window.
+    held_back: bool,
+}
+
+impl SelfLimit {
+    const INITIAL: usize = 32;
+    const MIN: usize = 4;
+    const MAX: usize = 4096;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            limit: Self::INITIAL,
+            window_spawned: 0,
+            window_completed: 0,
+            held_back: false,
+        }
+    }
+
+    /// Whether the body may run, given that `spawned` jobs were spawned so
+    /// far, of which `in_flight` have not completed.
+    pub(crate) fn admits(&mut self, spawned: u64, in_flight: usize) -> bool {
+        let completed = spawned - in_flight as u64;
+        let spawned_in_window = spawned - self.window_spawned;
+        let completed_in_window = completed - self.window_completed;
+        if completed_in_window >= self.limit as u64 {
+            if !self.held_back {
+                self.limit = (self.limit + 1).min(Self::MAX);
+            } else if spawned_in_window > completed_in_window {
+                self.limit = (self.limit - self.limit / 4).max(Self::MIN);
+            }
+            self.window_spawned = spawned;
+            self.window_completed = completed;
+            self.held_back = false;
+        }
+
+        let admits = in_flight < self.limit;
+        self.held_back |= !admits;
+        admits
+    }
+}
@@ -0,0 +1,40 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{channel::oneshot, future::Shared, Future, FutureExt};
+
+/// Handle to a job spawned with [`Scope::spawn_shared`][crate::Scope::spawn_shared].
+///
+/// The handle can be cloned, and every clone yields (a clone of) the result
+/// of the same job when awaited. The job runs once, whether or not any clone
+/// is awaited.
+pub struct SharedHandle<T> {
+    rx: Shared<oneshot::Receiver<T>>,
+}
+
+impl<T: Clone> SharedHandle<T> {
+    pub(crate) fn new(rx: oneshot::Receiver<T>) -> Self {
+        Self { rx: rx.shared() }
+    }
+}
+
+impl<T: Clone> Clone for SharedHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rx: self.rx.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Future for SharedHandle<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        Pin::new(&mut self.rx).poll(cx).map(|r| match r {
+            Ok(v) => v,
+            Err(e) => panic!("unexpected error: {e:?}"),
+        })
+    }
+}
@@ -1,16 +1,311 @@
 use std::pin::Pin;
 
 use crate::prelude::*;
-use crate::Scope;
-use futures::Future;
+use crate::{LocalScope, Scope};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
+use futures::{future::AbortHandle, Future};
+use pin_project::pin_project;
+
+/// Handle to a job spawned into a scope; awaiting it yields the job's result.
+///
+/// `Spawned<F>` is `Unpin` whenever `F` is, which is the case for the handles
+/// returned by [`Scope::spawn`]. This means they can be polled through
+/// `&mut` references and used with combinators such as
+/// [`select_all`](futures::future::select_all) without pinning them first.
+///
+/// # Examples
+///
+/// ```rust
+/// # futures::executor::block_on(async {
+/// let result = moro::async_scope!(|scope| {
+///     let handles = (0..3).map(|i| {
+///         scope.spawn(async move {
+///             if i != 1 {
+///                 futures::future::pending::<()>().await;
+///             }
+///             i * 22
+///         })
+///     });
+///     let (value, index, _rest) = futures::future::select_all(handles).await;
+///     scope.terminate((value, index)).await
+/// })
+/// .await;
+/// assert_eq!(result, (22, 1));
+/// # });
+/// ```
+///
+/// Handles are ordinary futures, so they can also be collected into a
+/// [`FuturesOrdered`](futures::stream::FuturesOrdered) to consume results in
+/// spawn order, whatever order the jobs complete in:
+///
+/// ```rust
+/// # use futures::{stream::FuturesOrdered, StreamExt};
+/// # use std::sync::Mutex;
+/// # use moro::test_util::yield_now;
+/// # futures::executor::block_on(async {
+/// let completed = Mutex::new(vec![]);
+/// let result = moro::async_scope!(|scope| {
+///     let handles: FuturesOrdered<_> = (0..3)
+///         .map(|i| {
+///             let completed = &completed;
+///             scope.spawn(async move {
+///                 // Earlier jobs take longer.
+///                 for _ in i..3 {
+///                     yield_now().await;
+///                 }
+///                 completed.lock().unwrap().push(i);
+///                 i
+///             })
+///         })
+///         .collect();
+///     handles.collect::<Vec<_>>().await
+/// })
+/// .await;
+/// assert_eq!(result, [0, 1, 2]);
+/// assert_eq!(completed.into_inner().unwrap(), [2, 1, 0]);
+/// # });
+/// ```
+#[pin_project]
 pub struct Spawned<F> {
+    #[pin]
     f: F,
+
+    /// Aborts the job; `None` if the job never ran.
+    abort: Option<AbortHandle>,
+
+    /// Set once the job's result is available to `f`.
+    finished: Arc<AtomicBool>,
+
+    /// Set once the result was taken with `try_take_output`.
+    taken: bool,
 }
 
 impl<F> Spawned<F> {
-    pub(crate) fn new(f: F) -> Self {
-        Self { f }
+    pub(crate) fn for_job(f: F, abort: AbortHandle, finished: Arc<AtomicBool>) -> Self {
+        Self {
+            f,
+            abort: Some(abort),
+            finished,
+            taken: false,
+        }
+    }
+
+    /// Handle of a job that was not admitted to run; `f` is ready right away.
+    pub(crate) fn rejected(f: F) -> Self {
+        Self {
+            f,
+            abort: None,
+            finished: Arc::new(AtomicBool::new(true)),
+            taken: false,
+        }
+    }
+
+    /// Returns true once the job has completed and its output is available,
+    /// so that awaiting this handle (or
+    /// [`try_take_output`][Self::try_take_output]) would not wait.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// moro::async_scope!(|scope| {
+    ///     let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    ///     let waiting = scope.spawn(async { rx.await.unwrap() });
+    ///     let quick = scope.spawn(async { 22 });
+    ///     // Let the jobs run.
+    ///     scope.spawn(async {}).await;
+    ///     assert!(quick.is_finished());
+    ///     assert!(!waiting.is_finished());
+    ///     tx.send(()).unwrap();
+    ///     waiting.await;
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Transforms the job's result with `f` once it is awaited.
+    ///
+    /// The job itself is unchanged and still runs as part of the scope; `f`
+    /// runs on the result when the returned handle is awaited, like
+    /// [`FutureExt::map`](futures::FutureExt::map). The handle keeps
+    /// supporting [`is_finished`][Self::is_finished] and
+    /// [`abort`][Self::abort].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result = moro::async_scope!(|scope| {
+    ///     let len = scope.spawn(async { "hello".to_string() }).map(|s| s.len());
+    ///     len.await
+    /// })
+    /// .await;
+    /// assert_eq!(result, 5);
+    /// # });
+    /// ```
+    pub fn map<U>(self, f: impl FnOnce(F::Output) -> U) -> Spawned<impl Future<Output = U>>
+    where
+        F: Future,
+    {
+        Spawned {
+            f: futures::FutureExt::map(self.f, f),
+            abort: self.abort,
+            finished: self.finished,
+            taken: self.taken,
+        }
+    }
+
+    /// Boxes the future awaited by this handle, so that the handle is `Unpin`
+    /// and has a nameable type.
+    ///
+    /// Handles returned by [`Scope::spawn`] and most other spawn methods are
+    /// already `Unpin`, so `&mut handle` can be awaited, or polled in a loop
+    /// of `select`s, without pinning. `boxed` covers the remaining cases, and
+    /// erases the handle's type, e.g. to store handles from different spawn
+    /// methods together. The job itself is not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let total = moro::async_scope!(|scope| {
+    ///     let handles: Vec<moro::Spawned<futures::future::BoxFuture<'_, u32>>> = vec![
+    ///         scope.spawn(async { 1 }).boxed(),
+    ///         scope.spawn(async { "22" }).map(|s| s.parse().unwrap()).boxed(),
+    ///     ];
+    ///     futures::future::join_all(handles).await.into_iter().sum::<u32>()
+    /// })
+    /// .await;
+    /// assert_eq!(total, 23);
+    /// # });
+    /// ```
+    pub fn boxed<'a>(self) -> Spawned<futures::future::BoxFuture<'a, F::Output>>
+    where
+        F: Future + Send + 'a,
+    {
+        Spawned {
+            f: Box::pin(self.f),
+            abort: self.abort,
+            finished: self.finished,
+            taken: self.taken,
+        }
+    }
+
+    /// Cancels this job, without affecting the rest of the scope.
+    ///
+    /// The job's future is dropped the next time the scope polls its jobs,
+    /// releasing whatever it holds, while the scope body and the other jobs
+    /// keep running. The handle is consumed, so the job's result can no
+    /// longer be awaited. This suits e.g. hedged requests, where only the
+    /// first response is needed. To cancel every job, terminate the scope
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// struct Replica<'a>(&'a AtomicBool);
+    ///
+    /// impl Drop for Replica<'_> {
+    ///     fn drop(&mut self) {
+    ///         self.0.store(true, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let slow_dropped = AtomicBool::new(false);
+    /// let result = moro::async_scope!(|scope| {
+    ///     let requests = [("fast", true), ("slow", false)].map(|(name, responds)| {
+    ///         let slow_dropped = &slow_dropped;
+    ///         scope.spawn(async move {
+    ///             if !responds {
+    ///                 let _replica = Replica(slow_dropped);
+    ///                 futures::future::pending::<()>().await;
+    ///             }
+    ///             name
+    ///         })
+    ///     });
+    ///     let (winner, _, rest) = futures::future::select_all(requests).await;
+    ///     for request in rest {
+    ///         request.abort();
+    ///     }
+    ///     // The scope keeps running after the abort.
+    ///     let other = scope.spawn(async { 22 }).await;
+    ///     (winner, other)
+    /// })
+    /// .await;
+    /// assert_eq!(result, ("fast", 22));
+    /// assert!(slow_dropped.load(Ordering::SeqCst));
+    /// # });
+    /// ```
+    pub fn abort(self) {
+        if let Some(abort) = self.abort {
+            abort.abort();
+        }
+    }
+}
+
+impl<F> Spawned<F>
+where
+    F: Future + Unpin,
+{
+    /// Takes the job's output if the job has completed, without waiting.
+    ///
+    /// Returns `None` if the job is still running, or if its output was
+    /// already taken. Once the output is taken, the handle must not be
+    /// awaited anymore. This allows harvesting the jobs that are done out of
+    /// a collection of handles, while the others keep running.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let harvested = moro::async_scope!(|scope| {
+    ///     let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    ///     let rx = futures::FutureExt::shared(rx);
+    ///     let mut handles: Vec<_> = (0..4)
+    ///         .map(|i| {
+    ///             let rx = rx.clone();
+    ///             scope.spawn(async move {
+    ///                 if i % 2 == 1 {
+    ///                     rx.await.unwrap();
+    ///                 }
+    ///                 i
+    ///             })
+    ///         })
+    ///         .collect();
+    ///     // Let the jobs run.
+    ///     scope.spawn(async {}).await;
+    ///     let mut harvested: Vec<_> =
+    ///         handles.iter_mut().filter_map(|h| h.try_take_output()).collect();
+    ///     handles.retain(|h| !h.is_finished());
+    ///     tx.send(()).unwrap();
+    ///     harvested.extend(futures::future::join_all(handles).await);
+    ///     harvested
+    /// })
+    /// .await;
+    /// assert_eq!(harvested, [0, 2, 1, 3]);
+    /// # });
+    /// ```
+    pub fn try_take_output(&mut self) -> Option<F::Output> {
+        if self.taken || !self.is_finished() {
+            return None;
+        }
+        let mut cx = std::task::Context::from_waker(futures::task::noop_waker_ref());
+        match Pin::new(&mut self.f).poll(&mut cx) {
+            std::task::Poll::Ready(output) => {
+                self.taken = true;
+                Some(output)
+            }
+            std::task::Poll::Pending => None,
+        }
     }
 }
 
@@ -21,13 +316,12 @@ where
     type Output = F::Output;
 
     fn poll(
-        self: std::pin::Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
-        unsafe {
-            let f = Pin::new_unchecked(&mut self.get_unchecked_mut().f);
-            F::poll(f, cx)
-        }
+        let this = self.project();
+        assert!(!*this.taken, "output already taken with `try_take_output`");
+        this.f.poll(cx)
     }
 }
 
@@ -37,6 +331,7 @@ where
     O: Send,
     E: Send,
 {
+    #[track_caller]
     pub fn or_cancel<'scope, 'env, T>(
         self,
         scope: &'scope Scope<'scope, 'env, Result<T, E>>,
@@ -48,4 +343,183 @@ where
     {
         scope.spawn(async { self.await.unwrap_or_cancel(scope).await })
     }
+
+    /// Like [`or_cancel`][Self::or_cancel], but converts the job's error into
+    /// the scope's error type with [`Into`], like the `?` operator does.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result: anyhow::Result<u32> = moro::async_scope!(|scope| {
+    ///     let parsed = scope.spawn(async { "22".parse::<u32>() }).or_cancel_into(scope);
+    ///     let opened = scope
+    ///         .spawn(async { std::fs::read_to_string("/does/not/exist") })
+    ///         .or_cancel_into(scope);
+    ///     let (parsed, _) = futures::join!(parsed, opened);
+    ///     Ok(parsed)
+    /// })
+    /// .await;
+    /// assert!(result.unwrap_err().downcast_ref::<std::io::Error>().is_some());
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn or_cancel_into<'scope, 'env, T, E2>(
+        self,
+        scope: &'scope Scope<'scope, 'env, Result<T, E2>>,
+    ) -> impl Future<Output = O> + 'scope
+    where
+        T: Send,
+        E2: Send,
+        E: Into<E2> + 'env,
+        O: 'scope,
+        F: 'scope,
+    {
+        scope.spawn(async { self.await.unwrap_or_cancel_into(scope).await })
+    }
+
+    /// Like [`or_cancel`][Self::or_cancel], for jobs of a [`LocalScope`].
+    #[track_caller]
+    pub fn or_cancel_local<'scope, 'env, T>(
+        self,
+        scope: &'scope LocalScope<'scope, 'env, Result<T, E>>,
+    ) -> impl Future<Output = O> + 'scope
+    where
+        O: 'scope,
+        F: 'scope,
+    {
+        scope.spawn(async move {
+            match self.await {
+                Ok(value) => value,
+                Err(error) => scope.terminate(Err(error)).await,
+            }
+        })
+    }
+
+    /// Like [`or_cancel`][Self::or_cancel], but only errors matching `fatal`
+    /// cancel the scope. Other errors are returned to the awaiter, to be
+    /// handled like any `Result`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[derive(Debug, PartialEq)]
+    /// enum Error {
+    ///     Retryable,
+    ///     Fatal,
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let result: Result<(), Error> = moro::async_scope!(|scope| {
+    ///     let fatal = |e: &Error| *e == Error::Fatal;
+    ///     let first = scope.spawn(async { Err::<(), _>(Error::Retryable) });
+    ///     // A recoverable error is simply returned.
+    ///     assert_eq!(first.cancel_if_err_matching(scope, fatal).await, Err(Error::Retryable));
+    ///
+    ///     let second = scope.spawn(async { Err::<(), _>(Error::Fatal) });
+    ///     // A fatal error cancels the scope, so this never returns.
+    ///     let _ = second.cancel_if_err_matching(scope, fatal).await;
+    ///     unreachable!()
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err(Error::Fatal));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn cancel_if_err_matching<'scope, 'env, T>(
+        self,
+        scope: &'scope Scope<'scope, 'env, Result<T, E>>,
+        fatal: impl Fn(&E) -> bool + Send + 'scope,
+    ) -> impl Future<Output = Result<O, E>> + 'scope
+    where
+        T: Send,
+        O: 'scope,
+        E: 'scope,
+        F: 'scope,
+    {
+        scope.spawn(async move {
+            match self.await {
+                Err(e) if fatal(&e) => scope.terminate(Err(e)).await,
+                result => result,
+            }
+        })
+    }
+}
+
+impl<F, O> Spawned<F>
+where
+    F: Future<Output = Option<O>> + Send,
+    O: Send,
+{
+    /// Like [`or_cancel`][Self::or_cancel], for jobs yielding an `Option`:
+    /// if the job yields `None`, the scope is terminated with `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// async fn total(prices: &HashMap<&str, u32>, items: &[&str]) -> Option<u32> {
+    ///     moro::async_scope!(|scope| {
+    ///         let mut total = 0;
+    ///         for item in items {
+    ///             let price = scope.spawn(async move { prices.get(item).copied() });
+    ///             total += price.or_none_cancel(scope).await;
+    ///         }
+    ///         Some(total)
+    ///     })
+    ///     .await
+    /// }
+    ///
+    /// # futures::executor::block_on(async {
+    /// let prices = HashMap::from([("apple", 3), ("pear", 4)]);
+    /// assert_eq!(total(&prices, &["apple", "pear"]).await, Some(7));
+    /// assert_eq!(total(&prices, &["apple", "kiwi"]).await, None);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn or_none_cancel<'scope, 'env, T>(
+        self,
+        scope: &'scope Scope<'scope, 'env, Option<T>>,
+    ) -> impl Future<Output = O> + 'scope
+    where
+        T: Send,
+        O: 'scope,
+        F: 'scope,
+    {
+        self.or_none_cancel_with(scope, || None)
+    }
+
+    /// Like [`or_none_cancel`][Self::or_none_cancel], but if the job yields
+    /// `None`, the scope is terminated with `value()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let result: Result<u32, String> = moro::async_scope!(|scope| {
+    ///     let user = scope.spawn(async { None::<u32> });
+    ///     Ok(user.or_none_cancel_with(scope, || Err("no such user".to_string())).await)
+    /// })
+    /// .await;
+    /// assert_eq!(result, Err("no such user".to_string()));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn or_none_cancel_with<'scope, 'env, R>(
+        self,
+        scope: &'scope Scope<'scope, 'env, R>,
+        value: impl FnOnce() -> R + Send + 'scope,
+    ) -> impl Future<Output = O> + 'scope
+    where
+        R: Send,
+        O: 'scope,
+        F: 'scope,
+    {
+        scope.spawn(async move {
+            match self.await {
+                Some(output) => output,
+                None => scope.terminate(value()).await,
+            }
+        })
+    }
 }
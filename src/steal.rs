@@ -0,0 +1,166 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use futures::{future::BoxFuture, stream::FuturesUnordered, Future, StreamExt};
+
+use crate::Scope;
+
+/// A job that has not been started; the future is only created when a
+/// worker picks the job up.
+type LazyJob = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// Work queues for a group of workers, each running its own scope, where an
+/// idle worker steals jobs that a busier sibling has not started yet.
+///
+/// This is basic work-stealing for structured fan-out, e.g. in a server
+/// running one scope per worker thread: jobs are pushed onto a worker's queue
+/// with [`push`][Self::push], and each worker runs
+/// [`run_worker`][Self::run_worker] within its scope. Whenever it has room for
+/// another job, a worker takes one from the front of its own queue; once that
+/// is empty, it steals from the back of the longest queue of a sibling.
+///
+/// Since a job may end up running in any worker's scope, it must be `Send`
+/// and `'static`: it cannot borrow from the scope it was meant for. Jobs are
+/// created lazily, so moving them between workers is cheap and the future is
+/// constructed by the worker that runs it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::sync::{Arc, Mutex};
+/// # futures::executor::block_on(async {
+/// let group = moro::StealableScopeGroup::new(2);
+/// let done = Arc::new(Mutex::new(vec![]));
+/// for i in 0..4 {
+///     // All jobs are pushed onto worker 0.
+///     let done = done.clone();
+///     group.push(0, move || async move { done.lock().unwrap().push(i) });
+/// }
+///
+/// let (ran0, ran1) = futures::join!(
+///     moro::async_scope!(|scope| group.run_worker(0, 2, scope).await),
+///     moro::async_scope!(|scope| group.run_worker(1, 2, scope).await),
+/// );
+///
+/// // Worker 1 had no jobs of its own and stole some from worker 0.
+/// assert!(ran1 > 0);
+/// assert_eq!(ran0 + ran1, 4);
+/// let mut done = done.lock().unwrap().clone();
+/// done.sort();
+/// assert_eq!(done, [0, 1, 2, 3]);
+/// # });
+/// ```
+pub struct StealableScopeGroup {
+    queues: Vec<Mutex<VecDeque<LazyJob>>>,
+}
+
+impl StealableScopeGroup {
+    /// Creates a group of `workers` workers with empty queues, numbered from
+    /// zero.
+    pub fn new(workers: usize) -> Self {
+        Self {
+            queues: (0..workers).map(|_| Default::default()).collect(),
+        }
+    }
+
+    /// Pushes a job onto the queue of `worker`. `make` is called to create
+    /// the job's future when a worker starts it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no worker numbered `worker`.
+    pub fn push<F>(&self, worker: usize, make: impl FnOnce() -> F + Send + 'static)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.queues[worker]
+            .lock()
+            .unwrap()
+            .push_back(Box::new(move || Box::pin(make())));
+    }
+
+    /// Number of jobs queued for any worker that have not been started.
+    pub fn len(&self) -> usize {
+        (0..self.queues.len()).map(|i| self.queued(i)).sum()
+    }
+
+    /// True if no worker has jobs queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of jobs queued for `worker` that have not been started.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no worker numbered `worker`.
+    pub fn queued(&self, worker: usize) -> usize {
+        self.queues[worker].lock().unwrap().len()
+    }
+
+    /// Runs jobs as `worker` in `scope`, up to `concurrency` at once, until
+    /// every queue of the group is empty. Returns the number of jobs this
+    /// worker ran.
+    ///
+    /// Jobs are taken from the queues one at a time, whenever fewer than
+    /// `concurrency` are running, so that the others stay available to be
+    /// stolen by idle siblings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no worker numbered `worker`, or if `concurrency`
+    /// is zero.
+    ///
+    /// # Examples
+    ///
+    /// Jobs of a single worker run concurrently, so they can wait on each
+    /// other:
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// let group = moro::StealableScopeGroup::new(1);
+    /// let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    /// group.push(0, move || async move { rx.await.unwrap() });
+    /// group.push(0, move || async move { tx.send(()).unwrap() });
+    /// assert_eq!(group.len(), 2);
+    ///
+    /// let ran = moro::async_scope!(|scope| group.run_worker(0, 2, scope).await).await;
+    /// assert_eq!(ran, 2);
+    /// assert!(group.is_empty());
+    /// # });
+    /// ```
+    pub async fn run_worker<'scope, R: Send>(
+        &self,
+        worker: usize,
+        concurrency: usize,
+        scope: &'scope Scope<'scope, '_, R>,
+    ) -> usize {
+        assert!(concurrency > 0, "a worker must be able to run a job");
+        let mut running = FuturesUnordered::new();
+        let mut ran = 0;
+        loop {
+            while running.len() < concurrency {
+                match self.take(worker) {
+                    Some(job) => running.push(scope.spawn(job())),
+                    None => break,
+                }
+            }
+            // Nothing is running only if there was nothing left to take.
+            if running.next().await.is_none() {
+                return ran;
+            }
+            ran += 1;
+        }
+    }
+
+    /// Takes the next job of `worker`, stealing one from a sibling if its own
+    /// queue is empty.
+    fn take(&self, worker: usize) -> Option<LazyJob> {
+        if let Some(job) = self.queues[worker].lock().unwrap().pop_front() {
+            return Some(job);
+        }
+        let victim = (0..self.queues.len())
+            .filter(|&i| i != worker)
+            .max_by_key(|&i| self.queued(i))?;
+        self.queues[victim].lock().unwrap().pop_back()
+    }
+}
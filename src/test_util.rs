@@ -0,0 +1,121 @@
+//! Fixtures for tests, shared by the examples in the documentation. Requires
+//! the `test-util` feature; not covered by semver guarantees.
+
+use std::{
+    sync::{Arc, Mutex},
+    task::{Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+
+use crate::{Clock, Scope};
+
+/// Yields to the executor once, waking the task right away, so that other
+/// jobs get to run; like `tokio::task::yield_now`, but for any executor.
+pub async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+/// Number of jobs `scope` can track without allocating, to observe
+/// [`Scope::shrink_to_fit`].
+pub fn job_capacity<R: Send>(scope: &Scope<'_, '_, R>) -> usize {
+    scope.job_capacity()
+}
+
+/// A simulated [`Clock`], whose time only moves when it is advanced, or when
+/// something sleeps: sleeping jumps ahead to the deadline right away.
+pub struct VirtualClock {
+    now: Mutex<Instant>,
+}
+
+impl VirtualClock {
+    /// Creates a clock starting at the current time.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            now: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Moves the clock's time forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        let mut now = self.now.lock().unwrap();
+        *now = (*now).max(deadline);
+        Box::pin(async {})
+    }
+}
+
+/// A simulated [`Clock`], whose time only moves when it is advanced. Unlike
+/// with [`VirtualClock`], sleeping waits until the clock has been advanced
+/// past the deadline, so tests control when each sleeper wakes up.
+pub struct ManualClock {
+    state: Arc<Mutex<ManualState>>,
+}
+
+struct ManualState {
+    now: Instant,
+    /// Wakers of the sleepers, woken on every advance.
+    sleepers: Vec<Waker>,
+}
+
+impl ManualClock {
+    /// Creates a clock starting at the current time.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ManualState {
+                now: Instant::now(),
+                sleepers: vec![],
+            })),
+        }
+    }
+
+    /// Moves the clock's time forward by `duration`; the sleepers whose
+    /// deadline has passed complete when next polled.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.now += duration;
+        for sleeper in state.sleepers.drain(..) {
+            sleeper.wake();
+        }
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.state.lock().unwrap().now
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        let state = self.state.clone();
+        Box::pin(std::future::poll_fn(move |cx| {
+            let mut state = state.lock().unwrap();
+            if state.now >= deadline {
+                Poll::Ready(())
+            } else {
+                state.sleepers.push(cx.waker().clone());
+                Poll::Pending
+            }
+        }))
+    }
+}
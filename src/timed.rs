@@ -0,0 +1,11 @@
+use std::time::Duration;
+
+/// The output of a job spawned with [`Scope::spawn_timed`][crate::Scope::spawn_timed].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timed<T> {
+    /// The value the job produced.
+    pub value: T,
+
+    /// Time from the first poll of the job until it completed.
+    pub elapsed: Duration,
+}
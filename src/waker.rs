@@ -0,0 +1,42 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use futures::task::{ArcWake, AtomicWaker};
+
+/// Wraps the waker of the task that polls a scope. Jobs are polled with this
+/// waker rather than the task's own waker.
+///
+/// When many jobs become ready during a single executor tick, each of them
+/// invokes `wake`, but only the first one is forwarded to the task. The flag is
+/// reset every time the scope polls its jobs, so we never lose a wakeup: any
+/// job that wakes after that point will wake the task again.
+pub(crate) struct CoalescingWaker {
+    waker: AtomicWaker,
+    notified: AtomicBool,
+}
+
+impl CoalescingWaker {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            waker: AtomicWaker::new(),
+            notified: AtomicBool::new(false),
+        })
+    }
+
+    /// Registers the waker of the task that is currently polling the scope
+    /// and re-arms the wakeup so the next notification is forwarded.
+    pub(crate) fn register(&self, waker: &std::task::Waker) {
+        self.waker.register(waker);
+        self.notified.store(false, Ordering::Release);
+    }
+}
+
+impl ArcWake for CoalescingWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        if !arc_self.notified.swap(true, Ordering::AcqRel) {
+            arc_self.waker.wake();
+        }
+    }
+}